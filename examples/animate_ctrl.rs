@@ -1,6 +1,7 @@
 use bevy::{dev_tools::fps_overlay::FpsOverlayPlugin, prelude::*};
 use bevy_flash::{
-    FlashCompleteEvent, FlashFrameEvent, FlashPlugin,
+    FlashAnimationInterrupted, FlashAnimationLooped, FlashCompleteEvent, FlashFrameEvent,
+    FlashPlugin,
     assets::Swf,
     player::{Flash, FlashPlayer, McRoot},
 };
@@ -23,6 +24,8 @@ fn main() {
         .add_systems(Update, animation_control)
         .add_observer(flash_complete)
         .add_observer(frame_event)
+        .add_observer(animation_looped)
+        .add_observer(animation_interrupted)
         .run();
 }
 
@@ -31,7 +34,10 @@ fn setup(mut commands: Commands, assert_server: Res<AssetServer>) {
     commands.spawn((
         Name::new("冲霄"),
         Flash(assert_server.load("spirit2159src.swf")),
-        FlashPlayer::from_animation_name("WAI"),
+        // ATT 等非循环动画播放完毕后回到循环播放的 WAI
+        FlashPlayer::from_animation_name("WAI")
+            .with_looping(true)
+            .with_fallback("WAI"),
         Transform::from_scale(Vec3::splat(1.0))
             .with_scale(Vec3::new(-2.0, -2.0, 1.0))
             .with_translation(Vec3::new(-200.0, 0.0, 0.0)),
@@ -45,7 +51,7 @@ fn setup(mut commands: Commands, assert_server: Res<AssetServer>) {
 
     // 提示按下空格键，触发动画 ATT 播放
     commands.spawn((
-        Text::new("按下空格键，触发动画 ATT 播放"),
+        Text::new("按下空格键，触发动画 ATT 播放；P 暂停/继续；左右方向键单步"),
         TextFont {
             font: assert_server.load("fonts/SourceHanSansCN-Normal.otf"),
             font_size: 24.0,
@@ -79,40 +85,68 @@ fn animation_control(
             }
         }
     }
+
+    for (_, mut player, mut root, flash) in player.iter_mut() {
+        let Some(swf) = swf_res.get(flash.id()) else {
+            continue;
+        };
+        // 暂停/继续
+        if keyboard_input.just_pressed(KeyCode::KeyP) {
+            if player.is_paused() {
+                player.resume();
+            } else {
+                player.pause();
+            }
+        }
+        // 单步
+        if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+            player.step_forward(swf, root.as_mut());
+        }
+        if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+            player.step_backward(swf, root.as_mut());
+        }
+    }
 }
 
-fn flash_complete(
-    complete: On<FlashCompleteEvent>,
-    mut player: Query<(&mut FlashPlayer, &Flash, &mut McRoot)>,
-    swf_res: Res<Assets<Swf>>,
-) {
-    let Ok((mut player, flash, mut root)) = player.get_mut(complete.event_target()) else {
-        return;
-    };
+fn flash_complete(complete: On<FlashCompleteEvent>) {
     if let Some(animation_name) = &complete.event().name() {
         info!(
             "实体: {}, 动画: {:?}, 播放完毕",
             complete.event_target(),
             animation_name
         );
-        let Some(swf) = swf_res.get(flash.id()) else {
-            return;
-        };
-        player.set_play("WAI", swf, root.as_mut());
-        player.set_looping(true);
     }
 }
 
+fn animation_looped(looped: On<FlashAnimationLooped>) {
+    info!(
+        "实体: {}, 动画: {:?}, 第 {} 次循环",
+        looped.event_target(),
+        looped.event().name(),
+        looped.event().loop_count()
+    );
+}
+
+fn animation_interrupted(interrupted: On<FlashAnimationInterrupted>) {
+    info!(
+        "实体: {}, 动画: {:?}, 被中断",
+        interrupted.event_target(),
+        interrupted.event().name()
+    );
+}
+
 /// 需要在 Flash 动画中添加标签，标签名称为事件名称。
-/// 事件标签格式: `event_<EventName>`
+/// 事件标签格式: `event_<EventName>`，可携带参数: `event_<EventName>:<key>=<value>,<key>=<value>`
 fn frame_event(frame_event: On<FlashFrameEvent>, mut player: Query<&mut FlashPlayer>) {
     let Ok(_player) = player.get_mut(frame_event.event_target()) else {
         return;
     };
-    let event_name = frame_event.event().name();
+    let event = frame_event.event();
     info!(
-        "实体: {}, 触发帧事件: {:?}",
+        "实体: {}, 触发帧事件: {:?}, 参数: {:?}, 子影片: {:?}",
         frame_event.event_target(),
-        event_name
+        event.name(),
+        event.params(),
+        event.path()
     );
 }
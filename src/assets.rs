@@ -1,10 +1,18 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use bevy::{
-    asset::{Asset, AssetLoader, AssetPath, Handle, LoadContext, RenderAssetUsages, io::Reader},
+    asset::{
+        Asset, AssetLoader, AssetPath, Handle, LoadContext, ReadAssetBytesError, RenderAssetUsages,
+        io::{AssetReaderError, Reader},
+    },
+    audio::AudioSource,
     color::{Color, ColorToComponents},
     image::Image,
-    log::error,
+    log::{error, warn},
     math::{Mat3, Mat4},
     mesh::{Indices, Mesh, PrimitiveTopology},
     platform::collections::HashMap,
@@ -13,6 +21,8 @@ use bevy::{
     render::render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
 use copyless::VecHelper;
+use lyon_tessellation::FillOptions;
+use serde::{Deserialize, Serialize};
 use swf::{CharacterId, GradientInterpolation};
 
 use crate::{
@@ -20,25 +30,34 @@ use crate::{
     swf_runtime::{
         character::{BitmapLibrary, Character},
         display_object::FrameNumber,
-        graphic::Graphic,
         movie_clip::MovieClip,
+        sound::{Pcm, SoundData, SoundStream},
         tag_utils::{self, SwfMovie},
-        tessellator::{DrawType, Gradient, ShapeTessellator},
+        tessellator::{DrawType, Gradient, ShapeTessellator, StrokeTransform},
+        text::{Font, GlyphShapes},
     },
 };
 
-/// 制作多大得渐变纹理，越大细节越丰富，但是内存占用也越大
-const GRADIENT_SIZE: usize = 256;
+/// 默认的渐变纹理大小，越大细节越丰富，但是内存占用也越大
+const DEFAULT_GRADIENT_SIZE: u32 = 256;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SwfAssetLabel {
     Shape(CharacterId),
+    /// 字体中的字形，(字体 ID, 字形索引)
+    Glyph(CharacterId, u32),
+    Sound(CharacterId),
+    /// 影片剪辑的流式声音，根影片的 ID 为 0
+    SoundStream(CharacterId),
 }
 
 impl std::fmt::Display for SwfAssetLabel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SwfAssetLabel::Shape(id) => f.write_str(&format!("Shape{id}")),
+            SwfAssetLabel::Glyph(font_id, index) => f.write_str(&format!("Glyph{font_id}_{index}")),
+            SwfAssetLabel::Sound(id) => f.write_str(&format!("Sound{id}")),
+            SwfAssetLabel::SoundStream(id) => f.write_str(&format!("SoundStream{id}")),
         }
     }
 }
@@ -58,16 +77,54 @@ pub struct MeshDraw {
 #[derive(Asset, TypePath, Deref, Clone, Debug)]
 pub struct Shape(pub Vec<MeshDraw>);
 
+/// 解码后的位图纹理
+#[derive(Debug, Clone)]
+pub struct BitmapTexture {
+    pub handle: Handle<Image>,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// SWF 中定义的声音
+#[derive(Debug, Clone)]
+pub(crate) struct SwfSound {
+    pub(crate) handle: Handle<AudioSource>,
+    /// 解码后的 PCM，按 `SoundInfo` 生成入点、出点与包络不同的变体时使用，MP3 声音没有
+    pub(crate) pcm: Option<Arc<Pcm>>,
+}
+
+/// 影片剪辑时间轴上的流式声音
+#[derive(Debug, Clone)]
+pub(crate) struct SwfSoundStream {
+    /// 完整的音轨，从第一个数据块开始播放时使用
+    pub(crate) handle: Handle<AudioSource>,
+    pub(crate) stream: SoundStream,
+}
+
 /// SWF 资产结构体，包含了 SWF 文件的相关信息。
 #[derive(Asset, TypePath)]
 pub struct Swf {
     /// 存储角色ID与形状资源句柄的映射关系
     pub shape_handles: HashMap<CharacterId, Handle<Shape>>,
+    /// 位图 ID 与解码后纹理的映射，运行时重新细分形状时使用
+    pub bitmaps: HashMap<CharacterId, BitmapTexture>,
     pub library: MovieLibrary,
     /// 动画名称，以及动画的起始帧和总帧长
     pub animations: HashMap<Box<str>, (FrameNumber, FrameNumber)>,
-    pub frame_events: HashMap<FrameNumber, Box<str>>,
+    /// 动画描述文件中定义的动画默认播放参数
+    pub animation_defaults: HashMap<Box<str>, AnimationDefaults>,
+    /// 根影片中每一帧的帧事件
+    pub frame_events: HashMap<FrameNumber, Vec<FrameEvent>>,
+    /// 根影片的帧标签
+    pub frame_labels: HashMap<Box<str>, FrameNumber>,
     pub swf_movie: Arc<SwfMovie>,
+    /// 子影片中皮肤帧标签的前缀
+    pub skin_prefix: String,
+    /// 运行时生成形状网格的设置
+    pub(crate) mesh_settings: MeshSettings,
+    pub(crate) sounds: HashMap<CharacterId, SwfSound>,
+    /// 影片剪辑 ID 与流式声音的映射，根影片的 ID 为 0
+    pub(crate) sound_streams: HashMap<CharacterId, SwfSoundStream>,
 }
 
 impl Swf {
@@ -75,28 +132,296 @@ impl Swf {
         &self.animations
     }
 
-    pub fn frame_events(&self) -> &HashMap<FrameNumber, Box<str>> {
+    /// 动画默认的播放参数
+    pub fn animation_defaults(&self) -> &HashMap<Box<str>, AnimationDefaults> {
+        &self.animation_defaults
+    }
+
+    pub fn frame_events(&self) -> &HashMap<FrameNumber, Vec<FrameEvent>> {
         &self.frame_events
     }
 
+    /// 根影片的帧标签
+    pub fn frame_labels(&self) -> &HashMap<Box<str>, FrameNumber> {
+        &self.frame_labels
+    }
+
     pub fn characters(&self) -> &HashMap<CharacterId, Character> {
         &self.library.characters
     }
+
+    /// 子影片中皮肤帧标签的前缀
+    pub fn skin_prefix(&self) -> &str {
+        &self.skin_prefix
+    }
+
+    /// SWF 头中定义的帧率
+    pub fn frame_rate(&self) -> f32 {
+        self.swf_movie.frame_rate()
+    }
+
+    /// `DefineSound` 定义的声音
+    pub fn sound(&self, id: CharacterId) -> Option<&Handle<AudioSource>> {
+        self.sounds.get(&id).map(|sound| &sound.handle)
+    }
+
+    /// 按导出名称（链接名或 AS3 类名）查找声音
+    pub fn sound_by_name(&self, name: &str) -> Option<&Handle<AudioSource>> {
+        self.library
+            .export_characters()
+            .get(name)
+            .and_then(|id| self.sound(*id))
+    }
+}
+
+#[cfg(test)]
+impl Swf {
+    /// 由标签构建测试用的 SWF 资产，动画范围由帧标签推断，不生成网格与声音子资源
+    pub(crate) fn from_tags(tags: &[swf::Tag<'_>]) -> Self {
+        let settings = SwfLoaderSettings::default();
+        let swf_movie = Arc::new(SwfMovie::from_tags(tags));
+        let mut root = MovieClip::new(swf_movie.clone());
+        let mut library = MovieLibrary::default();
+        root.preload(&mut library, &mut BitmapLibrary::default(), &mut None);
+        Self {
+            shape_handles: HashMap::new(),
+            bitmaps: HashMap::new(),
+            library,
+            animations: label_animations(root.frame_labels(), root.total_frames(), &settings),
+            animation_defaults: HashMap::new(),
+            frame_events: frame_events_from_labels(root.frame_labels(), &settings.event_prefix),
+            frame_labels: root.frame_labels().clone(),
+            swf_movie,
+            skin_prefix: settings.skin_prefix.clone(),
+            mesh_settings: MeshSettings::from(&settings),
+            sounds: HashMap::new(),
+            sound_streams: HashMap::new(),
+        }
+    }
+
+    /// 由帧标签构建测试用的 SWF 资产，`labels` 为帧标签所在的帧与帧标签
+    pub(crate) fn from_labels(total_frames: FrameNumber, labels: &[(FrameNumber, &str)]) -> Self {
+        let mut tags = Vec::new();
+        for frame in 1..=total_frames {
+            tags.extend(
+                labels
+                    .iter()
+                    .filter(|(label_frame, _)| *label_frame == frame)
+                    .map(|(_, label)| {
+                        swf::Tag::FrameLabel(swf::FrameLabel {
+                            label: swf::SwfStr::from_utf8_str(label),
+                            is_anchor: false,
+                        })
+                    }),
+            );
+            tags.push(swf::Tag::ShowFrame);
+        }
+        Self::from_tags(&tags)
+    }
+}
+
+/// 帧事件，帧标签格式为 `event_<name>`，可以携带参数：`event_<name>:<key>=<value>,<key>=<value>`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameEvent {
+    pub name: Box<str>,
+    pub params: BTreeMap<Box<str>, Box<str>>,
+}
+
+impl FrameEvent {
+    /// 解析去掉前缀后的帧标签，没有 `=` 的参数值为空
+    pub fn parse(label: &str) -> Self {
+        let (name, params) = label.split_once(':').unwrap_or((label, ""));
+        Self {
+            name: name.trim().into(),
+            params: params
+                .split(',')
+                .filter(|param| !param.trim().is_empty())
+                .map(|param| {
+                    let (key, value) = param.split_once('=').unwrap_or((param, ""));
+                    (key.trim().into(), value.trim().into())
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 从帧标签中收集帧事件，同一帧可以有多个帧事件
+pub(crate) fn frame_events_from_labels(
+    frame_labels: &HashMap<Box<str>, FrameNumber>,
+    prefix: &str,
+) -> HashMap<FrameNumber, Vec<FrameEvent>> {
+    let mut frame_events: HashMap<FrameNumber, Vec<FrameEvent>> = HashMap::new();
+    for (label, frame) in frame_labels {
+        if let Some(event) = label.strip_prefix(prefix) {
+            frame_events
+                .entry(*frame)
+                .or_default()
+                .push(FrameEvent::parse(event));
+        }
+    }
+    // 帧标签没有顺序，按名称排序保证同一帧的事件顺序稳定
+    frame_events
+        .values_mut()
+        .for_each(|events| events.sort_by(|a, b| a.name.cmp(&b.name)));
+    frame_events
 }
 
 #[derive(Default, Asset, TypePath)]
 pub struct MovieLibrary {
     characters: HashMap<CharacterId, Character>,
     export_characters: HashMap<String, CharacterId>,
+    fonts: HashMap<CharacterId, Arc<Font>>,
+    /// 解码后的声音，加载完成后转换为 `AudioSource` 子资源
+    sounds: HashMap<CharacterId, SoundData>,
+    /// 影片剪辑的流式声音，根影片的 ID 为 0
+    sound_streams: HashMap<CharacterId, SoundStream>,
 }
 
 impl MovieLibrary {
+    pub fn fonts(&self) -> &HashMap<CharacterId, Arc<Font>> {
+        &self.fonts
+    }
+    pub fn fonts_mut(&mut self) -> &mut HashMap<CharacterId, Arc<Font>> {
+        &mut self.fonts
+    }
+    pub fn characters(&self) -> &HashMap<CharacterId, Character> {
+        &self.characters
+    }
     pub fn characters_mut(&mut self) -> &mut HashMap<CharacterId, Character> {
         &mut self.characters
     }
+    /// 导出名称（AS2 的 `ExportAssets` 与 AS3 的 `SymbolClass`）与字符 ID 的映射
+    pub fn export_characters(&self) -> &HashMap<String, CharacterId> {
+        &self.export_characters
+    }
     pub fn export_characters_mut(&mut self) -> &mut HashMap<String, CharacterId> {
         &mut self.export_characters
     }
+    pub fn sounds_mut(&mut self) -> &mut HashMap<CharacterId, SoundData> {
+        &mut self.sounds
+    }
+    pub fn sound_streams_mut(&mut self) -> &mut HashMap<CharacterId, SoundStream> {
+        &mut self.sound_streams
+    }
+}
+
+/// 动画范围的约定
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationRangeConvention {
+    /// 动画从 `anim_<name>`（或不带前缀的标签）开始，到下一个动画标签之前结束
+    LabelGap,
+    /// 动画从 `anim_<name>` 开始，到 `end_<name>` 标签所在帧（包含）结束，
+    /// 没有结束标签的动画仍然使用 [`AnimationRangeConvention::LabelGap`]，不读取动画描述文件
+    #[default]
+    EndLabel,
+    /// 在 [`AnimationRangeConvention::EndLabel`] 的基础上，使用 SWF 旁的动画描述文件定义动画范围与播放参数，
+    /// 动画描述文件不存在时只使用帧标签
+    Sidecar,
+}
+
+/// SWF 加载设置，可以在 `.meta` 文件中配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwfLoaderSettings {
+    /// 动画范围的约定
+    pub animation_ranges: AnimationRangeConvention,
+    /// 动画描述文件路径，为 `None` 时使用 SWF 同名的 `.ron` 文件，不存在时使用同名的 `.json` 文件，
+    /// 仅在 [`AnimationRangeConvention::Sidecar`] 下使用
+    pub sidecar_path: Option<String>,
+    /// 动画起始帧标签的前缀
+    pub animation_prefix: String,
+    /// 动画结束帧标签的前缀，仅在 [`AnimationRangeConvention::EndLabel`] 及
+    /// [`AnimationRangeConvention::Sidecar`] 下使用
+    pub end_prefix: String,
+    /// 帧事件标签的前缀
+    pub event_prefix: String,
+    /// 子影片中皮肤帧标签的前缀
+    pub skin_prefix: String,
+    /// 渐变颜色表的大小，越大细节越丰富，但是内存占用也越大
+    pub gradient_size: u32,
+    /// 曲线细分的容差（像素），越小曲线越平滑，顶点也越多
+    pub tessellation_tolerance: f32,
+}
+
+impl Default for SwfLoaderSettings {
+    fn default() -> Self {
+        Self {
+            animation_ranges: AnimationRangeConvention::default(),
+            sidecar_path: None,
+            animation_prefix: "anim_".into(),
+            end_prefix: "end_".into(),
+            event_prefix: "event_".into(),
+            skin_prefix: "skin_".into(),
+            gradient_size: DEFAULT_GRADIENT_SIZE,
+            tessellation_tolerance: FillOptions::DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+/// 运行时生成形状网格的设置
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MeshSettings {
+    /// 渐变颜色表的大小
+    pub gradient_size: usize,
+    /// 曲线细分的容差
+    pub tolerance: f32,
+}
+
+impl From<&SwfLoaderSettings> for MeshSettings {
+    fn from(settings: &SwfLoaderSettings) -> Self {
+        Self {
+            gradient_size: settings.gradient_size.max(2) as usize,
+            tolerance: settings.tessellation_tolerance.max(f32::EPSILON),
+        }
+    }
+}
+
+/// 动画描述文件
+///
+/// ```ron
+/// (
+///     animations: {
+///         "WAI": (start: "anim_WAI", end: 40, looping: true),
+///         "ATT": (speed: 1.5),
+///     },
+/// )
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnimationSidecar {
+    #[serde(default)]
+    pub animations: BTreeMap<String, AnimationDefinition>,
+}
+
+/// 动画描述文件中的动画定义，未定义的范围使用帧标签推断的范围
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnimationDefinition {
+    /// 起始帧
+    #[serde(default)]
+    pub start: Option<FrameRef>,
+    /// 结束帧（包含）
+    #[serde(default)]
+    pub end: Option<FrameRef>,
+    /// 是否循环播放
+    #[serde(default)]
+    pub looping: Option<bool>,
+    /// 默认播放速度
+    #[serde(default)]
+    pub speed: Option<f32>,
+}
+
+/// 以帧号或帧标签表示的帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FrameRef {
+    Frame(FrameNumber),
+    Label(String),
+}
+
+/// 动画默认的播放参数，开始播放该动画时应用
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnimationDefaults {
+    pub looping: Option<bool>,
+    pub speed: Option<f32>,
 }
 
 #[derive(Default)]
@@ -105,13 +430,13 @@ pub(crate) struct SwfLoader;
 impl AssetLoader for SwfLoader {
     type Asset = Swf;
 
-    type Settings = ();
+    type Settings = SwfLoaderSettings;
 
     type Error = tag_utils::Error;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &(),
+        settings: &SwfLoaderSettings,
         load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut swf_data = Vec::new();
@@ -132,14 +457,17 @@ impl AssetLoader for SwfLoader {
 
         let color_material =
             load_context.add_labeled_asset("color_material".to_owned(), ColorMaterial::default());
+        let bitmap_textures = load_bitmap_textures(load_context, &bitmaps);
+        let mesh_settings = MeshSettings::from(settings);
 
         library.characters.values_mut().for_each(|v| {
             if let Character::Graphic(graphic) = v {
                 let shape = load_shape_mesh(
                     load_context,
-                    graphic,
-                    &bitmaps,
+                    graphic.shape(),
+                    &bitmap_textures,
                     &color_material,
+                    mesh_settings,
                     &mut image_index,
                     &mut mesh_index,
                     &mut material_index,
@@ -152,44 +480,123 @@ impl AssetLoader for SwfLoader {
                     ),
                 );
                 // 生成Mesh 后清除图形记录数据，后续不在需要。
-                graphic.shape_mut().shape.clear();
+                // 含有依赖屏幕缩放的线条的图形需要在渲染时重新细分，保留记录数据。
+                if !graphic.has_scale_dependent_strokes() {
+                    graphic.shape_mut().shape.clear();
+                }
+            }
+        });
+        // 细分静态文本用到的字形，动态文本的内容在运行时才确定，需要细分字体中所有的字形。
+        // 每个字形只细分一次
+        let mut used_glyphs = BTreeSet::new();
+        for character in library.characters.values() {
+            match character {
+                Character::Text(text) => used_glyphs.extend(text.used_glyphs()),
+                Character::EditText(edit_text) => {
+                    if let Some(font_id) = edit_text.outline_font_id()
+                        && let Some(font) = library.fonts.get(&font_id)
+                    {
+                        used_glyphs.extend((0..font.glyph_count()).map(|index| (font_id, index)));
+                    }
+                }
+                _ => {}
             }
+        }
+        let mut glyph_shapes = GlyphShapes::new();
+        for (font_id, index) in used_glyphs {
+            let Some(glyph) = library.fonts.get(&font_id).and_then(|f| f.glyph(index)) else {
+                continue;
+            };
+            let shape = load_shape_mesh(
+                load_context,
+                glyph,
+                &bitmap_textures,
+                &color_material,
+                mesh_settings,
+                &mut image_index,
+                &mut mesh_index,
+                &mut material_index,
+            );
+            glyph_shapes.insert(
+                (font_id, index),
+                load_context.add_labeled_asset(
+                    SwfAssetLabel::Glyph(font_id, index).to_string(),
+                    Shape(shape),
+                ),
+            );
+        }
+        let glyph_shapes = Arc::new(glyph_shapes);
+        library.characters.values_mut().for_each(|v| match v {
+            Character::Text(text) => text.set_glyph_shapes(&glyph_shapes),
+            Character::EditText(edit_text) => edit_text.set_glyph_shapes(glyph_shapes.clone()),
+            _ => {}
         });
+
         // 加载子资源
+        // 声音转换为 WAV 或 MP3 的音频子资源
+        let mut sounds = HashMap::new();
+        for (id, data) in library.sounds.drain() {
+            let (bytes, pcm) = match data {
+                SoundData::Pcm(pcm) => (pcm.to_wav(), Some(Arc::new(pcm))),
+                SoundData::Mp3(data) => (data, None),
+            };
+            let handle = load_context.add_labeled_asset(
+                SwfAssetLabel::Sound(id).to_string(),
+                AudioSource {
+                    bytes: bytes.into(),
+                },
+            );
+            sounds.insert(id, SwfSound { handle, pcm });
+        }
+        let mut sound_streams = HashMap::new();
+        for (id, stream) in library.sound_streams.drain() {
+            let handle = load_context.add_labeled_asset(
+                SwfAssetLabel::SoundStream(id).to_string(),
+                AudioSource {
+                    bytes: stream.audio_from(0).into(),
+                },
+            );
+            sound_streams.insert(id, SwfSoundStream { handle, stream });
+        }
+
+        // 导出名称也可能指向声音或文档类（ID 为 0）
         library.export_characters.values().for_each(|v| {
-            if let Character::MovieClip(_mc) = library.characters.get(v).unwrap() {}
+            if let Some(Character::MovieClip(_mc)) = library.characters.get(v) {}
         });
 
-        let mut animations = <HashMap<_, _>>::default();
-        let mut frame_events = <HashMap<_, _>>::default();
-        root.frame_labels().iter().for_each(|(k, v)| {
-            if let Some(anim_name) = k.strip_prefix("anim_") {
-                animations.insert(anim_name.into(), (*v, 0));
-            } else if let Some(event_name) = k.strip_prefix("event_") {
-                frame_events.insert(*v, event_name.into());
-            } else {
-                animations.insert(k.clone(), (*v, 0));
+        // 子影片的帧事件在运行时由子影片自身触发
+        library.characters.values_mut().for_each(|v| {
+            if let Character::MovieClip(movie_clip) = v {
+                movie_clip.init_frame_events(&settings.event_prefix);
             }
         });
-        // 根据animations 的 起始帧v.0 的值，使用第一个大于当前项的v.0减去当前项的v.0，得到动画的长度。
-        if !animations.is_empty() {
-            let mut anim_frames = animations.values_mut().collect::<Vec<_>>();
-            anim_frames.sort_by_key(|(start, _)| *start);
-            for i in 0..anim_frames.len() - 1 {
-                let (start, _) = *anim_frames[i];
-                let (end, _) = *anim_frames[i + 1];
-                let len = end - start;
-                anim_frames[i].1 = len;
-            }
-            let last: usize = anim_frames.len() - 1;
-            anim_frames[last].1 = root.total_frames() - anim_frames[last].0;
+        let frame_events = frame_events_from_labels(root.frame_labels(), &settings.event_prefix);
+        let mut animations = label_animations(root.frame_labels(), root.total_frames(), settings);
+        let mut animation_defaults = HashMap::new();
+        if settings.animation_ranges == AnimationRangeConvention::Sidecar
+            && let Some(sidecar) =
+                read_sidecar(load_context, settings.sidecar_path.as_deref()).await
+        {
+            apply_sidecar(
+                sidecar,
+                root.frame_labels(),
+                &mut animations,
+                &mut animation_defaults,
+            );
         }
         Ok(Swf {
             shape_handles,
+            bitmaps: bitmap_textures,
             library,
             animations,
+            animation_defaults,
             frame_events,
+            frame_labels: root.frame_labels().clone(),
             swf_movie,
+            skin_prefix: settings.skin_prefix.clone(),
+            mesh_settings,
+            sounds,
+            sound_streams,
         })
     }
 
@@ -198,20 +605,206 @@ impl AssetLoader for SwfLoader {
     }
 }
 
-fn load_shape_mesh(
+/// 根据帧标签推断动画范围，返回动画名称与起始帧、总帧长
+fn label_animations(
+    frame_labels: &HashMap<Box<str>, FrameNumber>,
+    total_frames: FrameNumber,
+    settings: &SwfLoaderSettings,
+) -> HashMap<Box<str>, (FrameNumber, FrameNumber)> {
+    let mut animations = HashMap::new();
+    let mut end_frames = HashMap::new();
+    frame_labels.iter().for_each(|(k, v)| {
+        if k.starts_with(settings.event_prefix.as_str()) {
+            return;
+        }
+        if let Some(anim_name) = k.strip_prefix(settings.animation_prefix.as_str()) {
+            animations.insert(Box::from(anim_name), (*v, 0));
+        } else if let Some(anim_name) = k
+            .strip_prefix(settings.end_prefix.as_str())
+            .filter(|_| settings.animation_ranges != AnimationRangeConvention::LabelGap)
+        {
+            end_frames.insert(anim_name, *v);
+        } else {
+            animations.insert(k.clone(), (*v, 0));
+        }
+    });
+    // 根据animations 的 起始帧v.0 的值，使用第一个大于当前项的v.0减去当前项的v.0，得到动画的长度。
+    let mut anim_frames = animations.iter_mut().collect::<Vec<_>>();
+    anim_frames.sort_by_key(|(_, (start, _))| *start);
+    for i in 0..anim_frames.len() {
+        let start = anim_frames[i].1.0;
+        anim_frames[i].1.1 = match end_frames.get(anim_frames[i].0.as_ref()) {
+            // 结束标签所在帧也属于该动画
+            Some(&end) if end >= start => end - start + 1,
+            _ => match anim_frames.get(i + 1) {
+                Some((_, (end, _))) => end - start,
+                None => total_frames - start,
+            },
+        };
+    }
+    animations
+}
+
+/// 读取动画描述文件，`sidecar_path` 为 `None` 时读取 SWF 旁同名的 `.ron` 文件，不存在时读取同名的 `.json` 文件。
+/// 动画描述文件是可选的，读取或解析失败时只记录警告，动画范围使用帧标签推断的结果
+async fn read_sidecar(
+    load_context: &mut LoadContext<'_>,
+    sidecar_path: Option<&str>,
+) -> Option<AnimationSidecar> {
+    let candidates = match sidecar_path {
+        Some(path) => vec![PathBuf::from(path)],
+        None => vec![
+            load_context.path().with_extension("ron"),
+            load_context.path().with_extension("json"),
+        ],
+    };
+    for path in candidates {
+        match load_context.read_asset_bytes(path.clone()).await {
+            Ok(bytes) => return parse_sidecar(&path, &bytes),
+            Err(ReadAssetBytesError::AssetReaderError(AssetReaderError::NotFound(_))) => {}
+            Err(e) => {
+                warn!("Couldn't read animation sidecar {}: {e}", path.display());
+                return None;
+            }
+        }
+    }
+    // 显式指定的动画描述文件不存在时提示，同名文件不存在属于正常情况
+    if let Some(path) = sidecar_path {
+        warn!("Animation sidecar {path} not found, using frame label ranges");
+    }
+    None
+}
+
+/// 按扩展名解析动画描述文件
+fn parse_sidecar(path: &Path, bytes: &[u8]) -> Option<AnimationSidecar> {
+    let sidecar = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::from_slice(bytes).map_err(|e| e.to_string())
+    } else {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_bytes(bytes)
+            .map_err(|e| e.to_string())
+    };
+    sidecar
+        .inspect_err(|e| warn!("Invalid animation sidecar {}: {e}", path.display()))
+        .ok()
+}
+
+/// 使用动画描述文件覆盖动画范围，并记录动画的默认播放参数
+fn apply_sidecar(
+    sidecar: AnimationSidecar,
+    frame_labels: &HashMap<Box<str>, FrameNumber>,
+    animations: &mut HashMap<Box<str>, (FrameNumber, FrameNumber)>,
+    animation_defaults: &mut HashMap<Box<str>, AnimationDefaults>,
+) {
+    let resolve = |frame: &FrameRef| match frame {
+        FrameRef::Frame(frame) => Some(*frame),
+        FrameRef::Label(label) => {
+            let frame = frame_labels.get(label.as_str()).copied();
+            if frame.is_none() {
+                warn!("Frame label '{}' in animation sidecar not found", label);
+            }
+            frame
+        }
+    };
+    for (name, definition) in sidecar.animations {
+        let name: Box<str> = name.into();
+        let range = animations.get(&name).copied();
+        let start = match &definition.start {
+            Some(start) => resolve(start),
+            None => range.map(|(start, _)| start),
+        };
+        let end = match &definition.end {
+            Some(end) => resolve(end),
+            None => range.map(|(start, total_frames)| start + total_frames.max(1) - 1),
+        };
+        match (start, end) {
+            (Some(start), Some(end)) if end >= start => {
+                animations.insert(name.clone(), (start, end - start + 1));
+            }
+            _ => {
+                error!(
+                    "Invalid range for animation '{}' in animation sidecar",
+                    name
+                );
+                continue;
+            }
+        }
+        if definition.looping.is_some() || definition.speed.is_some() {
+            animation_defaults.insert(
+                name,
+                AnimationDefaults {
+                    looping: definition.looping,
+                    speed: definition.speed,
+                },
+            );
+        }
+    }
+}
+
+/// 解码 SWF 中定义的所有位图，每个位图只生成一份纹理
+fn load_bitmap_textures(
     load_context: &mut LoadContext,
-    graphic: &Graphic,
     bitmaps: &BitmapLibrary,
+) -> HashMap<CharacterId, BitmapTexture> {
+    let mut bitmap_textures = HashMap::new();
+    for (id, compressed_bitmap) in bitmaps.iter() {
+        let decoded = match compressed_bitmap.decode() {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                error!("Failed to decode bitmap: {:?}", e);
+                continue;
+            }
+        };
+        let bitmap = decoded.into_rgba();
+        let texture = Image::new(
+            Extent3d {
+                width: bitmap.width(),
+                height: bitmap.height(),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            bitmap.data().to_vec(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        let size = compressed_bitmap.size();
+        bitmap_textures.insert(
+            *id,
+            BitmapTexture {
+                handle: load_context.add_labeled_asset(format!("bitmap_{id}"), texture),
+                width: size.width,
+                height: size.height,
+            },
+        );
+    }
+    bitmap_textures
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_shape_mesh(
+    load_context: &mut LoadContext,
+    shape: &swf::Shape,
+    bitmap_textures: &HashMap<CharacterId, BitmapTexture>,
     color_material: &Handle<ColorMaterial>,
+    mesh_settings: MeshSettings,
     image_index: &mut usize,
     mesh_index: &mut usize,
     material_index: &mut usize,
 ) -> Vec<MeshDraw> {
-    let mut tessellator = ShapeTessellator::default();
-    let shape = graphic.shape();
-    let lyon_mesh = tessellator.tessellate_shape(shape.into(), bitmaps);
+    let mut tessellator = ShapeTessellator::new(mesh_settings.tolerance);
+    let lyon_mesh =
+        tessellator.tessellate_shape(shape.into(), bitmap_textures, StrokeTransform::IDENTITY);
 
-    let gradient_texture = load_gradient_textures(lyon_mesh.gradients, load_context, image_index);
+    let gradient_texture = load_gradient_textures(
+        lyon_mesh.gradients,
+        mesh_settings.gradient_size,
+        load_context,
+        image_index,
+    );
 
     let mut mesh_material = Vec::new();
     let draws = lyon_mesh.draws;
@@ -278,6 +871,9 @@ fn load_shape_mesh(
                 });
             }
             DrawType::Bitmap(bitmap) => {
+                let Some(bitmap_texture) = bitmap_textures.get(&bitmap.bitmap_id) else {
+                    continue;
+                };
                 let mut positions = Vec::with_capacity(draw.vertices.len());
                 for vertex in &draw.vertices {
                     positions.alloc().init([vertex.x, vertex.y, 0.0]);
@@ -292,45 +888,20 @@ fn load_shape_mesh(
                 let mesh = load_context.add_labeled_asset(format!("mesh_{mesh_index}"), mesh);
                 *mesh_index += 1;
 
-                let texture_transform = bitmap.matrix;
-                if let Some(compressed_bitmap) = bitmaps.get(&bitmap.bitmap_id) {
-                    let decoded = match compressed_bitmap.decode() {
-                        Ok(decoded) => decoded,
-                        Err(e) => {
-                            error!("Failed to decode bitmap: {:?}", e);
-                            continue;
-                        }
-                    };
-                    let bitmap = decoded.into_rgba();
-                    let texture = Image::new(
-                        Extent3d {
-                            width: bitmap.width(),
-                            height: bitmap.height(),
-                            depth_or_array_layers: 1,
-                        },
-                        TextureDimension::D2,
-                        bitmap.data().to_vec(),
-                        TextureFormat::Rgba8UnormSrgb,
-                        RenderAssetUsages::RENDER_WORLD,
-                    );
-                    let texture =
-                        load_context.add_labeled_asset(format!("texture_{image_index}"), texture);
-                    *image_index += 1;
-                    let material = load_context.add_labeled_asset(
-                        format!("material_{material_index}"),
-                        BitmapMaterial {
-                            texture,
-                            texture_transform: Mat4::from_mat3(Mat3::from_cols_array_2d(
-                                &texture_transform,
-                            )),
-                        },
-                    );
-                    *material_index += 1;
-                    mesh_material.push(MeshDraw {
-                        mesh,
-                        material_type: MaterialType::Bitmap(material),
-                    });
-                }
+                let material = load_context.add_labeled_asset(
+                    format!("material_{material_index}"),
+                    BitmapMaterial {
+                        texture: bitmap_texture.handle.clone(),
+                        texture_transform: Mat4::from_mat3(Mat3::from_cols_array_2d(
+                            &bitmap.matrix,
+                        )),
+                    },
+                );
+                *material_index += 1;
+                mesh_material.push(MeshDraw {
+                    mesh,
+                    material_type: MaterialType::Bitmap(material),
+                });
             }
         }
     }
@@ -339,11 +910,12 @@ fn load_shape_mesh(
 
 fn load_gradient_textures(
     gradients: Vec<Gradient>,
+    gradient_size: usize,
     load_context: &mut LoadContext,
     i: &mut usize,
 ) -> Vec<(Handle<Image>, GradientUniforms)> {
     let mut gradient_textures = Vec::new();
-    for (texture, gradient_uniforms) in create_gradient_textures(gradients) {
+    for (texture, gradient_uniforms) in create_gradient_textures(gradients, gradient_size) {
         let handle = load_context.add_labeled_asset(format!("gradient_{i}"), texture);
         *i += 1;
         gradient_textures.push((handle, gradient_uniforms));
@@ -351,71 +923,20 @@ fn load_gradient_textures(
     gradient_textures
 }
 
-pub fn create_gradient_textures(gradients: Vec<Gradient>) -> Vec<(Image, GradientUniforms)> {
+pub fn create_gradient_textures(
+    gradients: Vec<Gradient>,
+    size: usize,
+) -> Vec<(Image, GradientUniforms)> {
     let mut gradient_textures = Vec::new();
     for gradient in gradients {
-        let colors = if gradient.records.is_empty() {
-            vec![0; GRADIENT_SIZE * 4]
-        } else {
-            let mut colors = vec![0; GRADIENT_SIZE * 4];
-            let convert = if gradient.interpolation == GradientInterpolation::LinearRgb {
-                |color| color
-            } else {
-                |color| color
-            };
-
-            for t in 0..GRADIENT_SIZE {
-                let mut last = 0;
-                let mut next = 0;
-                for (i, record) in gradient.records.iter().enumerate().rev() {
-                    if (record.ratio as usize) < t {
-                        last = i;
-                        next = (i + 1).min(gradient.records.len() - 1);
-                        break;
-                    }
-                }
-                assert!(last == next || last + 1 == next);
-                let last_record = &gradient.records[last];
-                let next_record = &gradient.records[next];
-                let factor = if next == last {
-                    0.0
-                } else {
-                    (t as f32 - last_record.ratio as f32)
-                        / (next_record.ratio as f32 - last_record.ratio as f32)
-                };
-
-                colors[t * 4] = lerp(
-                    convert(last_record.color.r as f32),
-                    convert(next_record.color.r as f32),
-                    factor,
-                ) as u8;
-                colors[(t * 4) + 1] = lerp(
-                    convert(last_record.color.g as f32),
-                    convert(next_record.color.g as f32),
-                    factor,
-                ) as u8;
-                colors[(t * 4) + 2] = lerp(
-                    convert(last_record.color.b as f32),
-                    convert(next_record.color.b as f32),
-                    factor,
-                ) as u8;
-                colors[(t * 4) + 3] = lerp(
-                    last_record.color.a as f32,
-                    next_record.color.a as f32,
-                    factor,
-                ) as u8;
-            }
-            colors
-        };
-
         let texture = Image::new(
             Extent3d {
-                width: GRADIENT_SIZE as u32,
+                width: size as u32,
                 height: 1,
                 depth_or_array_layers: 1,
             },
             TextureDimension::D2,
-            colors,
+            gradient_ramp(&gradient, size),
             TextureFormat::Rgba8UnormSrgb,
             RenderAssetUsages::RENDER_WORLD,
         );
@@ -425,14 +946,328 @@ pub fn create_gradient_textures(gradients: Vec<Gradient>) -> Vec<(Image, Gradien
     gradient_textures
 }
 
+/// 生成 `size` 个像素的渐变颜色表（RGBA8，sRGB 编码），首尾像素分别对应比例 0 与 255。
+///
+/// 线性 RGB 插值时先将端点颜色转换到线性空间插值，再编码回 sRGB，
+/// 纹理以 sRGB 格式采样后即为线性空间插值的结果。
+fn gradient_ramp(gradient: &Gradient, size: usize) -> Vec<u8> {
+    let mut colors = vec![0; size * 4];
+    if gradient.records.is_empty() {
+        return colors;
+    }
+    let interpolate: fn(u8, u8, f32) -> u8 =
+        if gradient.interpolation == GradientInterpolation::LinearRgb {
+            |a, b, factor| {
+                let a = srgb_to_linear(a as f32 / 255.0);
+                let b = srgb_to_linear(b as f32 / 255.0);
+                (linear_to_srgb(lerp(a, b, factor)) * 255.0).round() as u8
+            }
+        } else {
+            |a, b, factor| lerp(a as f32, b as f32, factor).round() as u8
+        };
+
+    let ratio_step = 255.0 / (size.max(2) - 1) as f32;
+    for t in 0..size {
+        let ratio = t as f32 * ratio_step;
+        let mut last = 0;
+        let mut next = 0;
+        for (i, record) in gradient.records.iter().enumerate().rev() {
+            if (record.ratio as f32) < ratio {
+                last = i;
+                next = (i + 1).min(gradient.records.len() - 1);
+                break;
+            }
+        }
+        assert!(last == next || last + 1 == next);
+        let last_record = &gradient.records[last];
+        let next_record = &gradient.records[next];
+        let factor = if next == last {
+            0.0
+        } else {
+            (ratio - last_record.ratio as f32)
+                / (next_record.ratio as f32 - last_record.ratio as f32)
+        };
+
+        let (last_color, next_color) = (&last_record.color, &next_record.color);
+        colors[t * 4] = interpolate(last_color.r, next_color.r, factor);
+        colors[(t * 4) + 1] = interpolate(last_color.g, next_color.g, factor);
+        colors[(t * 4) + 2] = interpolate(last_color.b, next_color.b, factor);
+        // 透明度始终线性插值
+        colors[(t * 4) + 3] = lerp(last_color.a as f32, next_color.a as f32, factor).round() as u8;
+    }
+    colors
+}
+
 /// 线性插值
 fn lerp(a: f32, b: f32, factor: f32) -> f32 {
     a + (b - a) * factor
 }
 
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum MaterialType {
     Color(Handle<ColorMaterial>),
     Gradient(Handle<GradientMaterial>),
     Bitmap(Handle<BitmapMaterial>),
 }
+
+#[cfg(test)]
+mod tests {
+    use swf::{Color, GradientInterpolation, GradientRecord, GradientSpread};
+
+    use bevy::platform::collections::HashMap;
+
+    use super::{
+        AnimationDefaults, AnimationDefinition, AnimationRangeConvention, AnimationSidecar,
+        DEFAULT_GRADIENT_SIZE, FrameEvent, FrameRef, SwfLoaderSettings, apply_sidecar,
+        gradient_ramp, label_animations,
+    };
+    use crate::swf_runtime::display_object::FrameNumber;
+    use crate::swf_runtime::{shape_utils::GradientType, tessellator::Gradient};
+
+    fn gradient(interpolation: GradientInterpolation, records: Vec<GradientRecord>) -> Gradient {
+        Gradient {
+            gradient_type: GradientType::Linear,
+            repeat_mode: GradientSpread::Pad,
+            focal_point: swf::Fixed8::ZERO,
+            interpolation,
+            records,
+        }
+    }
+
+    fn record(ratio: u8, r: u8, g: u8, b: u8, a: u8) -> GradientRecord {
+        GradientRecord {
+            ratio,
+            color: Color { r, g, b, a },
+        }
+    }
+
+    fn default_ramp(gradient: &Gradient) -> Vec<u8> {
+        gradient_ramp(gradient, DEFAULT_GRADIENT_SIZE as usize)
+    }
+
+    fn texel(ramp: &[u8], t: usize) -> [u8; 4] {
+        ramp[t * 4..t * 4 + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn srgb_ramp_interpolates_encoded_values() {
+        let ramp = default_ramp(&gradient(
+            GradientInterpolation::Rgb,
+            vec![record(0, 0, 0, 0, 255), record(255, 255, 255, 255, 255)],
+        ));
+        assert_eq!(texel(&ramp, 0), [0, 0, 0, 255]);
+        assert_eq!(texel(&ramp, 64), [64, 64, 64, 255]);
+        assert_eq!(texel(&ramp, 128), [128, 128, 128, 255]);
+        assert_eq!(texel(&ramp, 255), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn linear_rgb_ramp_interpolates_in_linear_space() {
+        let ramp = default_ramp(&gradient(
+            GradientInterpolation::LinearRgb,
+            vec![record(0, 0, 0, 0, 0), record(255, 255, 255, 255, 255)],
+        ));
+        assert_eq!(texel(&ramp, 0), [0, 0, 0, 0]);
+        // 线性空间的 25%、50% 灰度编码为 sRGB 后的值，透明度仍线性插值
+        assert_eq!(texel(&ramp, 64), [137, 137, 137, 64]);
+        assert_eq!(texel(&ramp, 128), [188, 188, 188, 128]);
+        assert_eq!(texel(&ramp, 255), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn ramp_pads_outside_records() {
+        let records = vec![record(64, 255, 0, 0, 255), record(192, 0, 0, 255, 0)];
+        let ramp = default_ramp(&gradient(GradientInterpolation::Rgb, records.clone()));
+        assert_eq!(texel(&ramp, 0), [255, 0, 0, 255]);
+        assert_eq!(texel(&ramp, 64), [255, 0, 0, 255]);
+        assert_eq!(texel(&ramp, 96), [191, 0, 64, 191]);
+        assert_eq!(texel(&ramp, 192), [0, 0, 255, 0]);
+        assert_eq!(texel(&ramp, 255), [0, 0, 255, 0]);
+
+        let ramp = default_ramp(&gradient(GradientInterpolation::LinearRgb, records));
+        assert_eq!(texel(&ramp, 96), [225, 0, 137, 191]);
+    }
+
+    #[test]
+    fn smaller_ramp_spans_all_records() {
+        let ramp = gradient_ramp(
+            &gradient(
+                GradientInterpolation::Rgb,
+                vec![record(0, 0, 0, 0, 255), record(255, 255, 255, 255, 255)],
+            ),
+            3,
+        );
+        assert_eq!(texel(&ramp, 0), [0, 0, 0, 255]);
+        assert_eq!(texel(&ramp, 1), [128, 128, 128, 255]);
+        assert_eq!(texel(&ramp, 2), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn frame_event_params_are_parsed() {
+        let event = FrameEvent::parse("hit:damage=10, box=2,critical");
+        assert_eq!(event.name.as_ref(), "hit");
+        assert_eq!(event.params.get("damage").map(AsRef::as_ref), Some("10"));
+        assert_eq!(event.params.get("box").map(AsRef::as_ref), Some("2"));
+        assert_eq!(event.params.get("critical").map(AsRef::as_ref), Some(""));
+
+        let event = FrameEvent::parse("footstep");
+        assert_eq!(event.name.as_ref(), "footstep");
+        assert!(event.params.is_empty());
+    }
+
+    fn frame_labels(labels: &[(&str, FrameNumber)]) -> HashMap<Box<str>, FrameNumber> {
+        labels
+            .iter()
+            .map(|(label, frame)| (Box::from(*label), *frame))
+            .collect()
+    }
+
+    /// 共 15 帧，`walk` 在第 8 帧结束，第 9-11 帧不属于任何动画
+    fn labels() -> HashMap<Box<str>, FrameNumber> {
+        frame_labels(&[
+            ("anim_idle", 1),
+            ("anim_walk", 5),
+            ("event_step", 7),
+            ("end_walk", 8),
+            ("end_run", 3),
+            ("anim_jump", 12),
+        ])
+    }
+
+    #[test]
+    fn end_labels_close_animations() {
+        let animations = label_animations(&labels(), 15, &SwfLoaderSettings::default());
+        assert_eq!(animations.len(), 3);
+        assert_eq!(animations.get("idle"), Some(&(1, 4)));
+        assert_eq!(animations.get("walk"), Some(&(5, 4)));
+        assert_eq!(animations.get("jump"), Some(&(12, 3)));
+        // 没有起始标签的结束标签被忽略
+        assert!(!animations.contains_key("run"));
+    }
+
+    #[test]
+    fn label_gaps_treat_end_labels_as_animations() {
+        let settings = SwfLoaderSettings {
+            animation_ranges: AnimationRangeConvention::LabelGap,
+            ..Default::default()
+        };
+        let animations = label_animations(&labels(), 15, &settings);
+        assert_eq!(animations.get("idle"), Some(&(1, 2)));
+        assert_eq!(animations.get("end_run"), Some(&(3, 2)));
+        assert_eq!(animations.get("walk"), Some(&(5, 3)));
+        assert_eq!(animations.get("end_walk"), Some(&(8, 4)));
+        assert!(!animations.contains_key("event_step"));
+    }
+
+    #[test]
+    fn sidecar_overrides_label_ranges() {
+        let labels = labels();
+        let mut animations = label_animations(&labels, 15, &SwfLoaderSettings::default());
+        let mut animation_defaults = HashMap::new();
+        let definitions = [
+            (
+                "walk",
+                AnimationDefinition {
+                    start: Some(FrameRef::Label("anim_walk".into())),
+                    end: Some(FrameRef::Frame(10)),
+                    looping: Some(true),
+                    ..Default::default()
+                },
+            ),
+            (
+                "idle",
+                AnimationDefinition {
+                    speed: Some(1.5),
+                    ..Default::default()
+                },
+            ),
+            (
+                "gap",
+                AnimationDefinition {
+                    start: Some(FrameRef::Frame(9)),
+                    end: Some(FrameRef::Label("anim_jump".into())),
+                    ..Default::default()
+                },
+            ),
+            (
+                "missing",
+                AnimationDefinition {
+                    start: Some(FrameRef::Label("anim_missing".into())),
+                    end: Some(FrameRef::Frame(4)),
+                    looping: Some(true),
+                    ..Default::default()
+                },
+            ),
+            (
+                "reversed",
+                AnimationDefinition {
+                    start: Some(FrameRef::Frame(5)),
+                    end: Some(FrameRef::Frame(3)),
+                    ..Default::default()
+                },
+            ),
+            ("unknown", AnimationDefinition::default()),
+        ];
+        let sidecar = AnimationSidecar {
+            animations: definitions
+                .into_iter()
+                .map(|(name, definition)| (name.to_owned(), definition))
+                .collect(),
+        };
+        apply_sidecar(sidecar, &labels, &mut animations, &mut animation_defaults);
+
+        assert_eq!(animations.get("walk"), Some(&(5, 6)));
+        assert_eq!(animations.get("idle"), Some(&(1, 4)));
+        assert_eq!(animations.get("gap"), Some(&(9, 4)));
+        assert_eq!(animations.get("jump"), Some(&(12, 3)));
+        // 帧标签不存在或范围无效的动画被忽略，也不会记录默认播放参数
+        assert!(!animations.contains_key("missing"));
+        assert!(!animations.contains_key("reversed"));
+        assert!(!animations.contains_key("unknown"));
+        assert_eq!(
+            animation_defaults.get("walk"),
+            Some(&AnimationDefaults {
+                looping: Some(true),
+                speed: None,
+            })
+        );
+        assert_eq!(
+            animation_defaults.get("idle"),
+            Some(&AnimationDefaults {
+                looping: None,
+                speed: Some(1.5),
+            })
+        );
+        assert_eq!(animation_defaults.len(), 2);
+    }
+
+    #[test]
+    fn default_convention_ignores_sidecars() {
+        assert_eq!(
+            SwfLoaderSettings::default().animation_ranges,
+            AnimationRangeConvention::EndLabel
+        );
+    }
+
+    #[test]
+    fn empty_gradient_is_transparent() {
+        let ramp = default_ramp(&gradient(GradientInterpolation::Rgb, Vec::new()));
+        assert!(ramp.iter().all(|&value| value == 0));
+    }
+}
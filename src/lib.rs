@@ -25,54 +25,77 @@ mod render;
 pub mod shape;
 pub(crate) mod swf_runtime;
 
+pub use swf_runtime::filter::{DisplacementMapFilter, DisplacementMapFilterMode};
+
+use std::collections::BTreeMap;
 use std::collections::btree_map::ValuesMut;
 
 use crate::{
-    assets::{Shape, Swf, SwfLoader},
+    assets::{
+        BitmapTexture, MaterialType, MeshDraw, MeshSettings, Shape, Swf, SwfLoader, SwfSound,
+        SwfSoundStream, create_gradient_textures,
+    },
     commands::{DrawShapes, OffscreenDrawShapes, ShapeCommand},
-    player::{Flash, FlashPlayer, FlashPlayerTimer, McRoot},
+    player::{
+        AnimationEvent, Flash, FlashAdvanceMode, FlashPlayer, FlashPlayerTimer, FlashSyncGroup,
+        McRoot,
+    },
     render::{
         ColorMaterialHandle, FilterTextureMesh, FlashRenderPlugin,
-        blend_pipeline::{BlendMode, TrivialBlend},
+        blend_pipeline::{BlendMode, ComplexBlend, TrivialBlend},
         material::{BitmapMaterial, ColorMaterial, GradientMaterial},
         offscreen_texture::OffscreenTexture,
     },
     shape::FlashShape,
     swf_runtime::{
-        display_object::{DisplayObject, ImageCache, ImageCacheInfo, TDisplayObject},
+        button::{PointerContext, PointerInput},
+        display_object::{DisplayObject, FrameNumber, ImageCache, ImageCacheInfo, TDisplayObject},
+        edit_text::DeviceText,
         filter::Filter,
+        graphic::StrokeShapeCache,
         matrix::Matrix,
         morph_shape::Frame,
         movie_clip::MovieClip,
+        sound::{SoundTarget, SoundVariant},
+        tessellator::{DrawType, Mesh as TessellatedMesh},
         transform::{Transform as SwfTransform, TransformStack},
     },
 };
 
 use bevy::{
-    app::{App, Plugin, PostUpdate},
-    asset::{AssetApp, Assets, Handle},
-    camera::visibility::VisibilityClass,
-    color::Color,
+    app::{App, FixedUpdate, Plugin, PostUpdate},
+    asset::{AssetApp, AssetId, Assets, Handle, RenderAssetUsages},
+    audio::{AudioPlayer, AudioSink, AudioSinkPlayback, AudioSource, PlaybackSettings},
+    camera::{Camera, visibility::VisibilityClass},
+    color::{Color, ColorToComponents},
     ecs::{
+        change_detection::{DetectChanges, DetectChangesMut, Mut},
+        component::Component,
         entity::{Entity, EntityHashMap},
         event::EntityEvent,
-        query::Without,
-        schedule::IntoScheduleConfigs,
+        hierarchy::ChildOf,
+        query::{With, Without},
+        schedule::{IntoScheduleConfigs, common_conditions::not},
         system::{Commands, Local, Query, Res, ResMut},
     },
     image::Image,
+    input::{ButtonInput, mouse::MouseButton},
     log::warn_once,
-    math::{IVec2, Mat4, UVec2, Vec3},
-    mesh::Mesh,
+    math::{IVec2, Mat3, Mat4, Quat, UVec2, Vec3},
+    mesh::{Indices, Mesh, PrimitiveTopology},
     platform::collections::HashMap,
+    sprite::{Anchor, Text2d},
+    text::{Justify, LineBreak, TextBounds, TextColor, TextFont, TextLayout},
     time::Time,
     transform::{
         TransformSystems,
         components::{GlobalTransform, Transform},
     },
+    window::{PrimaryWindow, Window},
 };
 
-use swf::{CharacterId, Rectangle, Twips};
+use copyless::VecHelper;
+use swf::{CharacterId, ColorTransform, Fixed8, Point, Rectangle, SoundEvent, SoundInfo, Twips};
 
 /// 用于缓存每个实体对应的显示对象
 #[derive(Default)]
@@ -80,12 +103,76 @@ struct DisplayObjectCache {
     morph_shape_frame_cache: HashMap<CharacterId, fnv::FnvHashMap<u16, Frame>>,
     layer_offscreen_cache: HashMap<String, Entity>,
     image_cache: HashMap<String, ImageCache>,
+    stroke_shape_cache: StrokeShapeCache,
+    /// 设备字体文本对应的 `Text2d` 子实体
+    device_text_entities: Vec<Entity>,
 
     /// 是否需要翻转 X 轴
     flip_x: bool,
     /// 是否需要翻转 Y 轴
     flip_y: bool,
 }
+/// 渲染时检测到的翻转，按钮的命中检测需要与渲染保持一致
+#[derive(Component, Clone, Copy)]
+struct RootFlip {
+    x: bool,
+    y: bool,
+}
+
+/// 时间轴播放的声音实例，作为 Flash 实体的子实体，播放完所有循环后销毁
+#[derive(Component)]
+struct SoundInstance {
+    id: CharacterId,
+    /// 当前这一轮之后还需要播放的次数
+    remaining_loops: u16,
+}
+
+/// 按 `SoundInfo` 生成的声音变体，每个声音的每种变体只生成一次
+#[derive(Default)]
+struct SoundVariants(HashMap<(AssetId<AudioSource>, SoundVariant), Handle<AudioSource>>);
+
+impl SoundVariants {
+    /// 按入点、出点与音量包络取得声音的变体，不需要变体时使用原始声音。
+    ///
+    /// MP3 声音没有解码后的 PCM，只支持循环次数，入点、出点与包络会被忽略
+    fn handle(
+        &mut self,
+        id: CharacterId,
+        sound: &SwfSound,
+        info: &SoundInfo,
+        audio_sources: &mut Assets<AudioSource>,
+    ) -> Handle<AudioSource> {
+        let variant = SoundVariant::from(info);
+        match &sound.pcm {
+            Some(pcm) if !variant.is_original() => self
+                .0
+                .entry((sound.handle.id(), variant))
+                .or_insert_with(|| {
+                    audio_sources.add(AudioSource {
+                        bytes: pcm.apply_sound_info(info).to_wav().into(),
+                    })
+                })
+                .clone(),
+            None if !variant.is_original() => {
+                warn_once!("Sound {id} is MP3 encoded, its in/out points and envelope are ignored");
+                sound.handle.clone()
+            }
+            _ => sound.handle.clone(),
+        }
+    }
+}
+
+/// 影片剪辑的流式声音实例，作为 Flash 实体的子实体，影片剪辑停止或移除时销毁
+#[derive(Component)]
+struct StreamInstance {
+    /// 影片剪辑的实例路径
+    path: String,
+    /// 影片剪辑 ID，根影片为 0
+    id: CharacterId,
+    /// 上一次同步时影片剪辑所在的帧
+    frame: FrameNumber,
+}
+
 /// Flash 插件，为 Bevy 引入 Flash 动画。
 pub struct FlashPlugin;
 
@@ -96,10 +183,20 @@ impl Plugin for FlashPlugin {
             .init_asset::<Swf>()
             .init_asset::<Shape>()
             .init_asset_loader::<SwfLoader>()
+            .init_resource::<FlashAdvanceMode>()
+            .add_systems(FixedUpdate, advance_animation.run_if(fixed_advance))
             // .add_systems(PostUpdate, prepare_shape_mesh)
             .add_systems(
                 PostUpdate,
-                (prepare_root_clip, advance_animation)
+                (
+                    prepare_root_clip,
+                    update_buttons,
+                    advance_animation.run_if(not(fixed_advance)),
+                    play_sounds,
+                    loop_sounds,
+                    sync_sound_streams,
+                    render_animation,
+                )
                     .chain()
                     .before(TransformSystems::Propagate),
             );
@@ -134,6 +231,10 @@ struct ImageCacheDraw {
     commands: Vec<ShapeCommand>,
     dirty: bool,
     size: UVec2,
+    /// 图层遮罩，渲染后图层会乘以遮罩纹理的 alpha
+    mask: Option<Handle<Image>>,
+    /// 复杂混合，渲染后将给定图层以对应的混合模式混合到此纹理上
+    blend: Option<(Handle<Image>, ComplexBlend)>,
 }
 
 struct RenderContext<'w> {
@@ -151,6 +252,8 @@ struct RenderContext<'w> {
     cache_draws: &'w mut Vec<ImageCacheDraw>,
     shape_handles: &'w mut HashMap<CharacterId, Handle<Shape>>,
     commands: Vec<ShapeCommand>,
+    /// 使用设备字体绘制的动态文本
+    device_texts: Vec<DeviceText>,
     scale: Vec3,
 
     // 缓存相关
@@ -159,11 +262,22 @@ struct RenderContext<'w> {
     morph_shape_cache: &'w mut HashMap<CharacterId, fnv::FnvHashMap<u16, Frame>>,
     /// Image 缓存,这里需要使用深度层级作为key
     image_cache: &'w mut HashMap<String, ImageCache>,
+    /// 按屏幕缩放重新细分的形状缓存，用于含有不缩放线条、细线的图形
+    stroke_shape_cache: &'w mut StrokeShapeCache,
+    /// 位图 ID 与解码后纹理的映射
+    bitmap_textures: &'w HashMap<CharacterId, BitmapTexture>,
+    /// 运行时生成形状网格的设置
+    mesh_settings: MeshSettings,
 
     /// 是否需要翻转 X 轴
     flip_x: bool,
     /// 是否需要翻转 Y 轴
     flip_y: bool,
+
+    /// 当前渲染目标覆盖的范围，复杂混合模式以此范围内已绘制的内容作为背景
+    bounds: Rectangle<Twips>,
+    /// 当前渲染目标是否为透明度组（父级为 Layer 混合模式），Alpha 与 Erase 混合模式需要透明度组
+    transparency_group: bool,
 }
 
 impl<'w> RenderContext<'w> {
@@ -177,6 +291,9 @@ impl<'w> RenderContext<'w> {
         morph_shape_cache: &'w mut HashMap<CharacterId, fnv::FnvHashMap<u16, Frame>>,
         transform_stack: &'w mut TransformStack,
         image_cache: &'w mut HashMap<String, ImageCache>,
+        stroke_shape_cache: &'w mut StrokeShapeCache,
+        bitmap_textures: &'w HashMap<CharacterId, BitmapTexture>,
+        mesh_settings: MeshSettings,
         cache_draws: &'w mut Vec<ImageCacheDraw>,
         shape_handles: &'w mut HashMap<CharacterId, Handle<Shape>>,
         filter_texture_mesh: &'w FilterTextureMesh,
@@ -195,13 +312,19 @@ impl<'w> RenderContext<'w> {
             cache_draws,
             shape_handles,
             commands: Vec::new(),
+            device_texts: Vec::new(),
             scale,
             morph_shape_cache,
             image_cache,
+            stroke_shape_cache,
+            bitmap_textures,
+            mesh_settings,
             filter_texture_mesh,
             color_material,
             flip_x,
             flip_y,
+            bounds: Default::default(),
+            transparency_group: false,
         }
     }
 
@@ -218,6 +341,82 @@ impl<'w> RenderContext<'w> {
             blend_mode,
         });
     }
+
+    /// 将运行时细分得到的网格转换为形状资源
+    pub fn add_tessellated_shape(&mut self, lyon_mesh: TessellatedMesh) -> Handle<Shape> {
+        let mut gradient_texture = Vec::new();
+        for (texture, gradient_uniforms) in
+            create_gradient_textures(lyon_mesh.gradients, self.mesh_settings.gradient_size)
+        {
+            gradient_texture.push((self.images.add(texture), gradient_uniforms));
+        }
+        let mut shape = Vec::new();
+        for draw in lyon_mesh.draws {
+            let mut positions = Vec::with_capacity(draw.vertices.len());
+            for vertex in &draw.vertices {
+                positions.alloc().init([vertex.x, vertex.y, 0.0]);
+            }
+            let mesh = Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::RENDER_WORLD,
+            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+            match &draw.draw_type {
+                DrawType::Color => {
+                    let mut colors = Vec::with_capacity(draw.vertices.len());
+                    for vertex in &draw.vertices {
+                        let linear_color = Color::srgba_u8(
+                            vertex.color.r,
+                            vertex.color.g,
+                            vertex.color.b,
+                            vertex.color.a,
+                        )
+                        .to_linear();
+                        colors.alloc().init(linear_color.to_f32_array());
+                    }
+                    let mesh = mesh
+                        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+                        .with_inserted_indices(Indices::U32(draw.indices));
+                    shape.push(MeshDraw {
+                        mesh: self.meshes.add(mesh),
+                        material_type: MaterialType::Color(self.color_material.clone()),
+                    });
+                }
+                DrawType::Gradient { matrix, gradient } => {
+                    let Some((texture, gradient)) = gradient_texture.get(*gradient).cloned() else {
+                        continue;
+                    };
+                    let mesh = mesh.with_inserted_indices(Indices::U32(draw.indices));
+                    let material = self.gradients.add(GradientMaterial {
+                        gradient,
+                        texture,
+                        texture_transform: Mat4::from_mat3(Mat3::from_cols_array_2d(matrix)),
+                    });
+                    shape.push(MeshDraw {
+                        mesh: self.meshes.add(mesh),
+                        material_type: MaterialType::Gradient(material),
+                    });
+                }
+                DrawType::Bitmap(bitmap) => {
+                    let Some(bitmap_texture) = self.bitmap_textures.get(&bitmap.bitmap_id) else {
+                        continue;
+                    };
+                    let mesh = mesh.with_inserted_indices(Indices::U32(draw.indices));
+                    let material = self.bitmaps.add(BitmapMaterial {
+                        texture: bitmap_texture.handle.clone(),
+                        texture_transform: Mat4::from_mat3(Mat3::from_cols_array_2d(
+                            &bitmap.matrix,
+                        )),
+                    });
+                    shape.push(MeshDraw {
+                        mesh: self.meshes.add(mesh),
+                        material_type: MaterialType::Bitmap(material),
+                    });
+                }
+            }
+        }
+        self.shapes.add(Shape(shape))
+    }
 }
 
 /// Flash 动画完成事件，非循环播放时触发
@@ -239,6 +438,68 @@ impl FlashCompleteEvent {
     }
 }
 
+/// Flash 动画开始事件，开始播放一个动画时触发
+#[derive(EntityEvent, Clone)]
+pub struct FlashAnimationStarted {
+    /// 实体
+    entity: Entity,
+    /// 开始播放的动画名
+    name: Option<String>,
+}
+
+impl FlashAnimationStarted {
+    /// 实体
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Flash 动画循环事件，循环动画每完成一轮时触发
+#[derive(EntityEvent, Clone)]
+pub struct FlashAnimationLooped {
+    /// 实体
+    entity: Entity,
+    /// 当前播放的动画名
+    name: Option<String>,
+    /// 已经完成的循环次数，从 1 开始
+    loop_count: u32,
+}
+
+impl FlashAnimationLooped {
+    /// 实体
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub fn loop_count(&self) -> u32 {
+        self.loop_count
+    }
+}
+
+/// Flash 动画中断事件，`FlashPlayer::set_play` 替换尚未播放完毕的动画时触发
+#[derive(EntityEvent, Clone)]
+pub struct FlashAnimationInterrupted {
+    /// 实体
+    entity: Entity,
+    /// 被中断的动画名
+    name: Option<String>,
+}
+
+impl FlashAnimationInterrupted {
+    /// 实体
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
 /// Flash 动画帧事件
 #[derive(EntityEvent, Clone)]
 pub struct FlashFrameEvent {
@@ -246,29 +507,129 @@ pub struct FlashFrameEvent {
     entity: Entity,
     /// 帧事件名
     name: String,
+    /// 帧事件参数
+    params: BTreeMap<Box<str>, Box<str>>,
+    /// 触发事件的子影片实例路径，根影片为空
+    path: String,
 }
 impl FlashFrameEvent {
     pub fn name(&self) -> &str {
         &self.name
     }
+    pub fn params(&self) -> &BTreeMap<Box<str>, Box<str>> {
+        &self.params
+    }
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(AsRef::as_ref)
+    }
+    /// 子影片实例路径，以 `.` 分隔，未命名的实例为 `depth_<深度>`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Flash 按钮点击事件，在按钮上按下并松开鼠标左键时触发
+#[derive(EntityEvent, Clone)]
+pub struct FlashButtonClicked {
+    /// 实体
+    entity: Entity,
+    /// 按钮的实例路径
+    instance_path: String,
+}
+
+impl FlashButtonClicked {
+    /// 实体
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    /// 按钮的实例路径，以 `.` 分隔，未命名的实例为 `depth_<深度>`
+    pub fn instance_path(&self) -> &str {
+        &self.instance_path
+    }
+}
+
+/// Flash 声音事件，时间轴上遇到 `StartSound` 或 `StartSound2` 时触发
+#[derive(EntityEvent, Clone)]
+pub struct FlashSoundEvent {
+    /// 实体
+    entity: Entity,
+    /// 声音的字符 ID
+    id: CharacterId,
+    /// 声音资源
+    sound: Handle<AudioSource>,
+    /// 同步方式为 Stop，表示停止该声音
+    stop: bool,
+    /// 遇到声音的子影片实例路径，根影片为空
+    path: String,
+}
+
+impl FlashSoundEvent {
+    /// 实体
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    pub fn id(&self) -> CharacterId {
+        self.id
+    }
+    pub fn sound(&self) -> &Handle<AudioSource> {
+        &self.sound
+    }
+    pub fn is_stop(&self) -> bool {
+        self.stop
+    }
+    /// 子影片实例路径，以 `.` 分隔，未命名的实例为 `depth_<深度>`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Flash 皮肤切换事件，皮肤应用到影片上时触发
+#[derive(EntityEvent, Clone)]
+pub struct FlashSkinChangedEvent {
+    /// 实体
+    entity: Entity,
+    /// 之前的皮肤
+    previous: Option<String>,
+    /// 当前的皮肤
+    skin: Option<String>,
+}
+
+impl FlashSkinChangedEvent {
+    /// 实体
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    pub fn previous(&self) -> Option<&str> {
+        self.previous.as_deref()
+    }
+    pub fn skin(&self) -> Option<&str> {
+        self.skin.as_deref()
+    }
 }
 
 /// 为Player实体添加Root MovieClip 组件
 fn prepare_root_clip(
     mut commands: Commands,
-    mut player: Query<(Entity, &mut FlashPlayer, &Flash), Without<McRoot>>,
+    mut player: Query<(Entity, &mut FlashPlayer, &mut FlashPlayerTimer, &Flash), Without<McRoot>>,
     swf_res: Res<Assets<Swf>>,
 ) {
-    for (entity, mut player, flash) in player.iter_mut() {
+    for (entity, mut player, mut timer, flash) in player.iter_mut() {
         let Some(swf) = swf_res.get(flash.id()) else {
             continue;
         };
         let mut root = McRoot(MovieClip::new(swf.swf_movie.clone()));
-        player.play_target_animation(swf, &mut root);
+        player.start_animation(swf, &mut root);
+        // 使用 SWF 头中的帧率（或 FlashPlayer 中覆盖的帧率）
+        timer.set_frame_rate(player.effective_frame_rate(swf));
         commands.entity(entity).insert(root);
     }
 }
 
+/// `FlashAdvanceMode::Fixed` 模式下动画在 `FixedUpdate` 中推进，帧事件也在其中触发
+fn fixed_advance(mode: Res<FlashAdvanceMode>) -> bool {
+    matches!(*mode, FlashAdvanceMode::Fixed(_))
+}
+
 /// 将所有离屏渲染实体标记为不活跃
 fn mark_offscreen_textures_inactive(offscreen_textures: &mut Query<&mut OffscreenTexture>) {
     offscreen_textures
@@ -298,11 +659,94 @@ fn handle_animation_complete(
     false
 }
 
+/// 处理动画队列，到达切换时机时播放队列中的下一个动画，返回是否发生了切换。
+///
+/// `at_end` 表示当前动画已经播放完毕，`on_frame_event` 表示当前帧触发了帧事件
+fn handle_animation_queue(
+    commands: &mut Commands,
+    entity: Entity,
+    player: &mut FlashPlayer,
+    root: &mut McRoot,
+    swf: &Swf,
+    at_end: bool,
+    on_frame_event: bool,
+) -> bool {
+    let Some(next) = player.next_queued(at_end, on_frame_event) else {
+        return false;
+    };
+    // 非循环动画播放完毕后切换时仍然触发完成事件
+    if at_end && !player.is_looping() && !player.completed() {
+        commands.trigger(FlashCompleteEvent {
+            entity,
+            name: player.current_animation().map(|s| s.into()),
+        });
+    }
+    player.play_queued(next, swf, root);
+    true
+}
+
+/// 推进一帧并触发这一帧的事件，非循环动画播放完毕停在最后一帧时返回 `false`
+fn advance_frame(
+    commands: &mut Commands,
+    entity: Entity,
+    player: &mut FlashPlayer,
+    root: &mut McRoot,
+    swf: &Swf,
+) -> bool {
+    // 往返播放到达最后一帧后开始倒放
+    player.turn_around();
+
+    // 处理动画队列
+    let at_end = player.is_completed();
+    let queued = handle_animation_queue(commands, entity, player, root, swf, at_end, false);
+
+    // 处理动画完成事件
+    if !queued && handle_animation_complete(commands, entity, player) {
+        return false;
+    }
+
+    // 处理循环播放逻辑
+    handle_animation_loop(player, root, swf);
+    trigger_animation_events(commands, entity, player);
+
+    // 更新动画帧并触发帧事件
+    let reverse = player.is_reversing();
+    let on_frame_event = update_animation_frame(commands, entity, player, root, swf, reverse);
+
+    // 在帧事件处切换动画
+    if on_frame_event {
+        handle_animation_queue(commands, entity, player, root, swf, false, true);
+        trigger_animation_events(commands, entity, player);
+    }
+    true
+}
+
 /// 处理循环播放逻辑
 fn handle_animation_loop(player: &mut FlashPlayer, root: &mut McRoot, swf: &Swf) {
     if player.is_looping() && player.is_completed() {
         // 循环播放，跳回当前动画第一帧
-        player.play_target_animation(swf, root);
+        player.loop_animation(swf, root);
+    }
+}
+
+/// 触发动画开始、循环与中断事件
+fn trigger_animation_events(commands: &mut Commands, entity: Entity, player: &mut FlashPlayer) {
+    for event in player.take_animation_events() {
+        match event {
+            AnimationEvent::Started(name) => {
+                commands.trigger(FlashAnimationStarted { entity, name });
+            }
+            AnimationEvent::Looped(name, loop_count) => {
+                commands.trigger(FlashAnimationLooped {
+                    entity,
+                    name,
+                    loop_count,
+                });
+            }
+            AnimationEvent::Interrupted(name) => {
+                commands.trigger(FlashAnimationInterrupted { entity, name });
+            }
+        }
     }
 }
 
@@ -313,31 +757,506 @@ fn update_animation_frame(
     player: &mut FlashPlayer,
     root: &mut MovieClip,
     swf: &Swf,
-) {
+    reverse: bool,
+) -> bool {
     let characters = &swf.characters();
-    // 进入一帧
-    root.enter_frame(characters);
-    player.incr_frame();
+    if reverse {
+        // 倒放时跳转回上一帧，子影片按根时间轴推算的进度重建
+        player.decr_frame();
+        root.goto_frame(characters, player.root_frame(), false);
+        root.sync_children_to_timeline(characters);
+    } else {
+        // 进入一帧
+        root.enter_frame(characters);
+        player.incr_frame();
+    }
 
-    // 触发帧事件
-    if let Some(event) = swf.frame_events().get(&root.current_frame()) {
+    // 触发根影片的帧事件，再触发子影片的帧事件
+    let mut events = swf
+        .frame_events()
+        .get(&root.current_frame())
+        .map(|events| {
+            events
+                .iter()
+                .map(|event| (String::new(), event.clone()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    root.collect_child_frame_events(&mut Vec::new(), &mut events);
+    let mut sounds = Vec::new();
+    root.take_sounds(&mut Vec::new(), &mut sounds);
+    player.push_sounds(sounds);
+    let fired = !events.is_empty();
+    for (path, event) in events {
         commands.trigger(FlashFrameEvent {
             entity,
-            name: event.as_ref().into(),
+            name: event.name.into(),
+            params: event.params,
+            path,
         });
     }
+    fired
 }
 
-/// 推进Flash动画
-#[allow(clippy::too_many_arguments)]
+/// 应用皮肤，子影片可能随时间轴重新创建，因此每帧都需要应用
+fn apply_skin(
+    commands: &mut Commands,
+    entity: Entity,
+    player: &mut FlashPlayer,
+    root: &mut MovieClip,
+    swf: &Swf,
+) {
+    let characters = swf.characters();
+    if let Some(previous) = player.take_skin_change() {
+        root.release_skin(characters, swf.skin_prefix());
+        commands.trigger(FlashSkinChangedEvent {
+            entity,
+            previous,
+            skin: player.skin().map(Into::into),
+        });
+    }
+    if let Some(skin) = player.skin() {
+        root.apply_skin(characters, swf.skin_prefix(), skin);
+    }
+}
+
+/// 按鼠标指针与左键更新按钮的状态，并触发按钮点击事件
+#[allow(clippy::type_complexity)]
+fn update_buttons(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+    mut player: Query<(
+        Entity,
+        &mut FlashPlayer,
+        &mut McRoot,
+        &Flash,
+        &GlobalTransform,
+        Option<&RootFlip>,
+    )>,
+    swf_res: Res<Assets<Swf>>,
+) {
+    // 指针在世界坐标系下的位置
+    let cursor = windows
+        .single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .and_then(|cursor| {
+            cameras
+                .iter()
+                .filter(|(camera, _)| camera.is_active)
+                .find_map(|(camera, transform)| camera.viewport_to_world_2d(transform, cursor).ok())
+        });
+    let input = mouse.map_or_else(PointerInput::default, |mouse| PointerInput {
+        pressed: mouse.pressed(MouseButton::Left),
+        just_pressed: mouse.just_pressed(MouseButton::Left),
+        just_released: mouse.just_released(MouseButton::Left),
+    });
+    for (entity, mut player, mut root, flash, global_transform, flip) in player.iter_mut() {
+        let Some(swf) = swf_res.get(flash.id()) else {
+            continue;
+        };
+        // Flash 坐标系的 Y 轴向下
+        let position = cursor.map(|cursor| {
+            let local = global_transform
+                .affine()
+                .inverse()
+                .transform_point3(cursor.extend(0.0));
+            Point::new(
+                Twips::from_pixels(local.x as f64),
+                Twips::from_pixels(-local.y as f64),
+            )
+        });
+        let mut context = PointerContext {
+            input,
+            flip_x: flip.is_some_and(|flip| flip.x),
+            flip_y: flip.is_some_and(|flip| flip.y),
+            ..Default::default()
+        };
+        // 只在按钮状态变化时重新绘制，避免每帧都标记根影片被修改
+        if root.bypass_change_detection().update_buttons(
+            swf.characters(),
+            position,
+            &mut context,
+            true,
+        ) {
+            player.request_redraw();
+        }
+        for instance_path in context.clicked {
+            commands.trigger(FlashButtonClicked {
+                entity,
+                instance_path,
+            });
+        }
+    }
+}
+
+/// 推进Flash动画，领导者推进后，同步组中的跟随者在同一次更新中逐帧镜像领导者。
+///
+/// 按时间推进时运行在 `PostUpdate`，固定帧数推进时运行在 `FixedUpdate`
+#[allow(clippy::type_complexity)]
 fn advance_animation(
     time: Res<Time>,
+    advance_mode: Res<FlashAdvanceMode>,
+    mut commands: Commands,
+    mut players: Query<(
+        Entity,
+        &mut FlashPlayer,
+        &mut FlashPlayerTimer,
+        &mut McRoot,
+        &Flash,
+        Option<&FlashSyncGroup>,
+    )>,
+    swf_res: Res<Assets<Swf>>,
+) {
+    // 每个播放器本次更新中逐帧的播放状态，供跟随者镜像
+    let mut synced: EntityHashMap<Vec<SyncFrame>> = EntityHashMap::default();
+    for (entity, mut player, mut timer, mut root, swf, group) in players.iter_mut() {
+        if group.is_some() {
+            continue;
+        }
+        let Some(swf) = swf_res.get(swf.id()) else {
+            continue;
+        };
+        // 帧率可能在运行时被覆盖，每次推进前同步定时器
+        timer.set_frame_rate(player.effective_frame_rate(swf));
+        // 暂停时不推进定时器，跳转或单步后仍需要重新绘制当前帧
+        let frames = match *advance_mode {
+            _ if player.is_paused() => 0,
+            FlashAdvanceMode::Time => {
+                timer.tick_frames(time.delta().mul_f32(player.effective_speed()))
+            }
+            FlashAdvanceMode::Fixed(frames) => frames,
+        };
+        // 触发上一次推进之后（例如手动切换动画时）产生的动画事件
+        trigger_animation_events(&mut commands, entity, &mut player);
+        // 推进经过的每一帧，跳过的帧同样会触发事件，但只渲染最后一帧
+        let mut steps = Vec::new();
+        for _ in 0..frames {
+            if !advance_frame(&mut commands, entity, &mut player, &mut root, swf) {
+                break;
+            }
+            player.request_redraw();
+            steps.push(SyncFrame::from(&*player));
+        }
+        // 没有推进时仍需同步暂停状态与手动跳转的帧
+        if steps.is_empty() {
+            steps.push(SyncFrame::from(&*player));
+        }
+        synced.insert(entity, steps);
+    }
+
+    // 按同步链的层级依次推进跟随者，保证链式同步时上一级已经完成同步
+    let leaders: EntityHashMap<Entity> = players
+        .iter()
+        .filter_map(|(entity, .., group)| group.map(|group| (entity, group.leader())))
+        .collect();
+    let mut followers: Vec<(usize, Entity)> = leaders
+        .keys()
+        .filter_map(|&entity| sync_depth(&leaders, entity).map(|depth| (depth, entity)))
+        .collect();
+    followers.sort_unstable();
+    for (_, entity) in followers {
+        let Some(steps) = synced.get(&leaders[&entity]).cloned() else {
+            continue;
+        };
+        let Ok((_, mut player, _, mut root, swf, _)) = players.get_mut(entity) else {
+            continue;
+        };
+        let Some(swf) = swf_res.get(swf.id()) else {
+            continue;
+        };
+        let mut follower_steps = Vec::with_capacity(steps.len());
+        for step in &steps {
+            follow_frame(&mut commands, entity, &mut player, &mut root, swf, step);
+            follower_steps.push(SyncFrame::from(&*player));
+        }
+        synced.insert(entity, follower_steps);
+    }
+}
+
+/// 同步组中一帧的播放状态
+#[derive(Debug, Clone)]
+struct SyncFrame {
+    animation: Option<String>,
+    frame: FrameNumber,
+    paused: bool,
+}
+
+impl From<&FlashPlayer> for SyncFrame {
+    fn from(player: &FlashPlayer) -> Self {
+        Self {
+            animation: player.current_animation().map(ToOwned::to_owned),
+            frame: player.current_frame(),
+            paused: player.is_paused(),
+        }
+    }
+}
+
+/// 跟随者到最终领导者的层级，同步组成环（包括以自身为领导者）时返回 `None`
+fn sync_depth(leaders: &EntityHashMap<Entity>, entity: Entity) -> Option<usize> {
+    let mut depth = 0;
+    let mut current = entity;
+    while let Some(&leader) = leaders.get(&current) {
+        depth += 1;
+        if depth > leaders.len() {
+            warn_once!("FlashSyncGroup of {entity} forms a cycle and is ignored");
+            return None;
+        }
+        current = leader;
+    }
+    Some(depth)
+}
+
+/// 跟随者镜像领导者的一帧，跟随者的 SWF 中没有对应的动画时保持当前动画，只同步帧与暂停状态。
+///
+/// 相邻的帧按正常路径推进，子影片随之播放并触发帧事件；循环或跳转时直接跳到对应的帧。
+/// 只有动画或帧发生变化时才修改根影片，避免跟随者每次更新都被标记为需要重新绘制
+fn follow_frame(
+    commands: &mut Commands,
+    entity: Entity,
+    player: &mut FlashPlayer,
+    root: &mut Mut<McRoot>,
+    swf: &Swf,
+    target: &SyncFrame,
+) {
+    if let Some(animation) = &target.animation
+        && player.current_animation() != Some(animation.as_str())
+        && swf.animations().contains_key(animation.as_str())
+    {
+        player.set_play(animation, swf, root);
+    }
+    if target.paused {
+        player.pause();
+    } else {
+        player.resume();
+    }
+    let frame = target.frame.clamp(1, player.total_frames().max(1));
+    let current = player.current_frame();
+    if frame.abs_diff(current) == 1 {
+        update_animation_frame(commands, entity, player, root, swf, frame < current);
+        player.request_redraw();
+    } else if frame != current {
+        player.seek(frame, swf, root);
+    }
+    trigger_animation_events(commands, entity, player);
+}
+
+/// 播放推进时遇到的声音并触发声音事件。
+///
+/// 同步方式为 Start 的声音正在播放时不会重复播放，Stop 会停止该声音所有正在播放的实例
+fn play_sounds(
+    mut commands: Commands,
+    mut player: Query<(Entity, &mut FlashPlayer, &Flash)>,
+    instances: Query<(Entity, &SoundInstance, &ChildOf)>,
+    swf_res: Res<Assets<Swf>>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+    mut sound_variants: Local<SoundVariants>,
+) {
+    for (entity, mut player, flash) in player.iter_mut() {
+        let sounds = player.bypass_change_detection().take_sounds();
+        if sounds.is_empty() {
+            continue;
+        }
+        let Some(swf) = swf_res.get(flash.id()) else {
+            continue;
+        };
+        let mut playing: Vec<(Entity, CharacterId)> = instances
+            .iter()
+            .filter(|(_, _, child_of)| child_of.parent() == entity)
+            .map(|(instance, sound, _)| (instance, sound.id))
+            .collect();
+        for (path, start_sound) in sounds {
+            let id = match &start_sound.target {
+                SoundTarget::Id(id) => Some(*id),
+                SoundTarget::Class(name) => swf.library.export_characters().get(&**name).copied(),
+            };
+            let Some((id, sound)) = id.and_then(|id| swf.sounds.get(&id).map(|sound| (id, sound)))
+            else {
+                continue;
+            };
+            let info = &start_sound.info;
+            let stop = matches!(info.event, SoundEvent::Stop);
+            commands.trigger(FlashSoundEvent {
+                entity,
+                id,
+                sound: sound.handle.clone(),
+                stop,
+                path,
+            });
+            if !player.sounds_enabled() {
+                continue;
+            }
+            match info.event {
+                SoundEvent::Stop => playing.retain(|&(instance, playing_id)| {
+                    if playing_id == id {
+                        commands.entity(instance).despawn();
+                    }
+                    playing_id != id
+                }),
+                SoundEvent::Start if playing.iter().any(|&(_, playing_id)| playing_id == id) => {}
+                _ => {
+                    let handle = sound_variants.handle(id, sound, info, &mut audio_sources);
+                    let instance = spawn_sound(&mut commands, entity, id, handle, info);
+                    playing.push((instance, id));
+                }
+            }
+        }
+    }
+}
+
+/// 使流式声音与时间轴同步：影片剪辑跳转后从新的帧重新播放，随播放器暂停与恢复，
+/// 并按播放速度调整音调。倒放时不播放流式声音
+#[allow(clippy::type_complexity)]
+fn sync_sound_streams(
+    time: Res<Time>,
+    mut commands: Commands,
+    player: Query<(Entity, &FlashPlayer, &McRoot, &Flash)>,
+    mut instances: Query<(
+        Entity,
+        &mut StreamInstance,
+        &ChildOf,
+        Option<&mut AudioSink>,
+    )>,
+    swf_res: Res<Assets<Swf>>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    for (entity, player, root, flash) in player.iter() {
+        let Some(swf) = swf_res.get(flash.id()) else {
+            continue;
+        };
+        let mut streams = Vec::new();
+        if player.sounds_enabled() && !player.is_reversing() && !swf.sound_streams.is_empty() {
+            root.collect_sound_streams(
+                &|id| swf.sound_streams.contains_key(&id),
+                &mut Vec::new(),
+                &mut streams,
+            );
+        }
+        // 播放完毕的非循环动画不再推进，与暂停相同
+        let halted = player.is_paused() || (!player.is_looping() && player.completed());
+        let speed = player.effective_speed();
+        // 一次更新中正常推进的最大帧数，超过时视为跳转
+        let max_advance = (time.delta_secs() * player.effective_frame_rate(swf) * speed).ceil()
+            as FrameNumber
+            + 1;
+        for (instance, mut stream_instance, child_of, sink) in instances.iter_mut() {
+            if child_of.parent() != entity {
+                continue;
+            }
+            let position = streams.iter().position(|(path, id, _)| {
+                *path == stream_instance.path && *id == stream_instance.id
+            });
+            let Some(position) = position else {
+                commands.entity(instance).despawn();
+                continue;
+            };
+            let frame = streams[position].2;
+            if frame < stream_instance.frame || frame - stream_instance.frame > max_advance {
+                // 保留在列表中，下面从新的帧重新生成
+                commands.entity(instance).despawn();
+                continue;
+            }
+            streams.swap_remove(position);
+            stream_instance.frame = frame;
+            if let Some(sink) = sink {
+                if halted != sink.is_paused() {
+                    if halted {
+                        sink.pause();
+                    } else {
+                        sink.play();
+                    }
+                }
+                if sink.speed() != speed {
+                    sink.set_speed(speed);
+                }
+            }
+        }
+        if halted {
+            continue;
+        }
+        for (path, id, frame) in streams {
+            let Some(stream) = swf.sound_streams.get(&id) else {
+                continue;
+            };
+            let Some(handle) = stream_audio(stream, frame, &mut audio_sources) else {
+                continue;
+            };
+            // 播放完毕的实例保留到影片剪辑跳转或停止，以免重复播放
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((
+                    AudioPlayer::new(handle),
+                    PlaybackSettings::ONCE.with_speed(speed),
+                    StreamInstance { path, id, frame },
+                ));
+            });
+        }
+    }
+}
+
+/// 从 `frame` 所在的数据块开始的音频，从第一个数据块开始时使用加载时生成的完整音轨
+fn stream_audio(
+    stream: &SwfSoundStream,
+    frame: FrameNumber,
+    audio_sources: &mut Assets<AudioSource>,
+) -> Option<Handle<AudioSource>> {
+    match stream.stream.offset_at(frame)? {
+        0 => Some(stream.handle.clone()),
+        offset => Some(audio_sources.add(AudioSource {
+            bytes: stream.stream.audio_from(offset).into(),
+        })),
+    }
+}
+
+/// 作为 Flash 实体的子实体播放声音，循环由 `loop_sounds` 在每一轮结束后重新播放
+fn spawn_sound(
+    commands: &mut Commands,
+    entity: Entity,
+    id: CharacterId,
+    handle: Handle<AudioSource>,
+    info: &SoundInfo,
+) -> Entity {
+    let sound = SoundInstance {
+        id,
+        remaining_loops: info.num_loops.saturating_sub(1),
+    };
+    let mut instance = Entity::PLACEHOLDER;
+    commands.entity(entity).with_children(|parent| {
+        instance = parent
+            .spawn((AudioPlayer::new(handle), PlaybackSettings::ONCE, sound))
+            .id();
+    });
+    instance
+}
+
+/// 声音播放完一轮后，还有剩余循环时重新播放，否则销毁声音实例
+fn loop_sounds(
+    mut commands: Commands,
+    mut instances: Query<(Entity, &mut SoundInstance, &AudioSink)>,
+) {
+    for (instance, mut sound, sink) in instances.iter_mut() {
+        if !sink.empty() {
+            continue;
+        }
+        if sound.remaining_loops > 0 {
+            sound.remaining_loops -= 1;
+            // 移除播放器后，音频系统会重新播放 `AudioPlayer` 中的声音
+            commands.entity(instance).remove::<AudioSink>();
+        } else {
+            commands.entity(instance).despawn();
+        }
+    }
+}
+
+/// 渲染推进后或需要重新绘制的Flash动画
+#[allow(clippy::too_many_arguments)]
+fn render_animation(
     filter_texture_mesh: Res<FilterTextureMesh>,
     mut commands: Commands,
     mut player: Query<(
         Entity,
         &mut FlashPlayer,
-        &mut FlashPlayerTimer,
         &mut McRoot,
         &mut Transform,
         &Flash,
@@ -356,165 +1275,635 @@ fn advance_animation(
     let mut current_live_player = vec![];
     // 1. 将动画的每一帧将离屏渲染实体列为不活跃
     mark_offscreen_textures_inactive(&mut offscreen_textures);
-    // 2. 更新动画帧
-    for (entity, mut player, mut timer, mut root, mut transform, swf, global_transform) in
-        player.iter_mut()
-    {
+    // 2. 渲染推进后或需要重新绘制的动画
+    for (entity, mut player, mut root, mut transform, swf, global_transform) in player.iter_mut() {
         current_live_player.push(entity);
-        if timer
-            .tick(time.delta().mul_f32(player.speed()))
-            .just_finished()
-        {
-            // 处理动画完成事件
-            if handle_animation_complete(&mut commands, entity, &mut player) {
-                continue;
+        let Some(swf) = swf_res.get_mut(swf.id()) else {
+            continue;
+        };
+        // 根影片被修改（例如设置动态文本）时也需要重新绘制
+        if player.take_redraw() || root.is_changed() {
+            // 应用皮肤
+            apply_skin(&mut commands, entity, &mut player, &mut root, swf);
+
+            let display_object_cache = display_object_entity_caches.entry(entity).or_default();
+            // 处理翻转缩放
+            let flip_x = &mut display_object_cache.flip_x;
+            let flip_y = &mut display_object_cache.flip_y;
+            let global_scale = global_transform.scale();
+            let mut flipped = false;
+            if transform.scale.x < 0.0 {
+                transform.scale.x = transform.scale.x.abs();
+                *flip_x = true;
+                flipped = true;
             }
+            if transform.scale.y < 0.0 {
+                transform.scale.y = transform.scale.y.abs();
+                *flip_y = true;
+                flipped = true;
+            }
+            if flipped {
+                commands.entity(entity).insert(RootFlip {
+                    x: *flip_x,
+                    y: *flip_y,
+                });
+            }
+
+            let morph_shape_cache: &mut _ = &mut display_object_cache.morph_shape_frame_cache;
+            let image_cache = &mut display_object_cache.image_cache;
+
+            // 处理DisplayList
+            let mut cache_draws = vec![];
+            let mut transform_stack = TransformStack::default();
+            // 创建渲染上下文
+            let mut context = RenderContext::new(
+                shapes.as_mut(),
+                meshes.as_mut(),
+                images.as_mut(),
+                gradients.as_mut(),
+                bitmaps.as_mut(),
+                morph_shape_cache,
+                &mut transform_stack,
+                image_cache,
+                &mut display_object_cache.stroke_shape_cache,
+                &swf.bitmaps,
+                swf.mesh_settings,
+                &mut cache_draws,
+                &mut swf.shape_handles,
+                filter_texture_mesh.as_ref(),
+                &color_material.0,
+                global_scale,
+                *flip_x,
+                *flip_y,
+            );
+            context.bounds = root_render_bounds(&mut root, &mut context);
+            process_display_list(
+                root.render_list_mut(),
+                &mut context,
+                swf::BlendMode::Normal,
+                String::from("0"),
+                true,
+            );
+            commands.entity(entity).insert(DrawShapes(context.commands));
+            spawn_device_texts(
+                &mut commands,
+                entity,
+                context.device_texts,
+                &mut display_object_cache.device_text_entities,
+            );
+
+            // 处理离屏绘制
+            spawn_offscreen_texture(
+                &mut commands,
+                entity,
+                cache_draws,
+                &mut offscreen_textures,
+                display_object_cache,
+                global_scale,
+            );
+            display_object_cache.stroke_shape_cache.evict_unused();
+        }
+    }
+    display_object_entity_caches.retain(|entity, _| current_live_player.contains(entity));
+}
+
+/// 缓存信息
+struct CacheInfo {
+    image_info: ImageCacheInfo,
+    dirty: bool,
+    base_transform: SwfTransform,
+    bounds: Rectangle<Twips>,
+    draw_offset: IVec2,
+    filters: Vec<Filter>,
+}
+
+/// 处理显示对象列表，遍历并渲染每个显示对象
+fn process_display_list(
+    display_list: ValuesMut<'_, u16, DisplayObject>,
+    context: &mut RenderContext<'_>,
+    blend_mode: swf::BlendMode,
+    shape_depth_layer: String,
+    is_root: bool,
+) {
+    let mut display_objects: Vec<&mut DisplayObject> = display_list.collect();
+    process_display_objects(
+        &mut display_objects,
+        context,
+        blend_mode,
+        &shape_depth_layer,
+        is_root,
+    );
+}
+
+/// 按深度顺序渲染显示对象，遇到遮罩时将其与被遮罩的对象一起处理
+fn process_display_objects(
+    display_objects: &mut [&mut DisplayObject],
+    context: &mut RenderContext<'_>,
+    blend_mode: swf::BlendMode,
+    shape_depth_layer: &str,
+    is_root: bool,
+) {
+    let mut index = 0;
+    while index < display_objects.len() {
+        let clip_depth = display_objects[index].clip_depth();
+        if clip_depth > 0 && display_objects[index].allow_as_mask() {
+            // 遮罩作用于深度在 (depth, clip_depth] 范围内的兄弟对象
+            let end = display_objects[index + 1..]
+                .iter()
+                .position(|display_object| display_object.depth() > clip_depth)
+                .map_or(display_objects.len(), |position| index + 1 + position);
+            let (mask, maskees) = display_objects[index..end].split_first_mut().unwrap();
+            render_masked(
+                mask,
+                maskees,
+                context,
+                blend_mode,
+                shape_depth_layer,
+                is_root,
+            );
+            index = end;
+        } else if !display_objects[index].visible() {
+            // 不可见的对象连同其子对象都不渲染
+            index += 1;
+        } else {
+            render_display_list_child(
+                &mut *display_objects[index],
+                context,
+                blend_mode,
+                shape_depth_layer,
+                is_root,
+            );
+            index += 1;
+        }
+    }
+}
+
+/// 渲染显示列表中的单个显示对象
+fn render_display_list_child(
+    display_object: &mut DisplayObject,
+    context: &mut RenderContext<'_>,
+    blend_mode: swf::BlendMode,
+    shape_depth_layer: &str,
+    is_root: bool,
+) {
+    let id = display_object.id();
+    let shape_depth_layer = format!("{}_{}_{}", shape_depth_layer, display_object.depth(), id);
+
+    let transform = display_object_transform(display_object, context, is_root);
+    // 保存当前变换状态
+    context.transform_stack.push(&transform);
+
+    // 确定混合模式
+    let blend_mode = determine_blend_mode(blend_mode, display_object);
+
+    if blend_mode == swf::BlendMode::Layer {
+        render_blend_group(display_object, context, blend_mode, &shape_depth_layer);
+    } else if let BlendMode::Complex(complex_blend) = BlendMode::from(blend_mode) {
+        render_complex_blend(display_object, context, complex_blend, &shape_depth_layer);
+    } else if blend_mode != swf::BlendMode::Normal
+        && matches!(
+            display_object,
+            DisplayObject::MovieClip(_) | DisplayObject::Button(_)
+        )
+    {
+        // 影片剪辑的子对象需要先合成为一个整体，再以混合模式绘制，
+        // 单个图形则可以直接在渲染管线中混合
+        render_blend_group(display_object, context, blend_mode, &shape_depth_layer);
+    } else {
+        render_display_object_content(display_object, context, blend_mode, &shape_depth_layer);
+    }
+
+    // 恢复变换状态
+    context.transform_stack.pop();
+}
+
+/// 处理缓存和滤镜后渲染显示对象，调用前显示对象的变换需要已经压入变换栈
+fn render_display_object_content(
+    display_object: &mut DisplayObject,
+    context: &mut RenderContext<'_>,
+    blend_mode: swf::BlendMode,
+    shape_depth_layer: &str,
+) {
+    // 处理缓存和滤镜
+    let cache_info = process_cache_and_filters(
+        display_object,
+        context,
+        display_object.id(),
+        shape_depth_layer,
+    );
+
+    // 根据是否有缓存信息选择渲染方式
+    if let Some(cache_info) = cache_info {
+        render_with_cache(
+            display_object,
+            context,
+            cache_info,
+            blend_mode,
+            shape_depth_layer,
+        );
+    } else {
+        // 直接渲染显示对象
+        render_child(display_object, context, blend_mode, shape_depth_layer);
+    }
+}
+
+/// 计算根显示列表在实体坐标系下的渲染边界
+fn root_render_bounds(root: &mut MovieClip, context: &mut RenderContext<'_>) -> Rectangle<Twips> {
+    let mut bounds: Option<Rectangle<Twips>> = None;
+    for display_object in root
+        .render_list_mut()
+        .filter(|display_object| display_object.visible())
+    {
+        let matrix = display_object_transform(display_object, context, true).matrix;
+        let child_bounds = display_object.render_bounds_with_transform(&matrix, true, context);
+        bounds = Some(match bounds {
+            Some(bounds) => bounds.union(&child_bounds),
+            None => child_bounds,
+        });
+    }
+    bounds
+        .map(|bounds| snap_bounds_to_pixels(&bounds))
+        .unwrap_or_default()
+}
+
+/// 将显示对象作为一个整体以混合模式渲染。
+///
+/// 显示对象及其子对象先以 Normal 混合模式渲染到一个独立的离屏图层，
+/// 再将图层以 `blend_mode` 绘制到当前上下文。Layer 混合模式下图层同时作为透明度组。
+fn render_blend_group(
+    display_object: &mut DisplayObject,
+    context: &mut RenderContext<'_>,
+    blend_mode: swf::BlendMode,
+    shape_depth_layer: &str,
+) {
+    let matrix = context.transform_stack.transform().matrix;
+    let bounds = display_object.render_bounds_with_transform(&matrix, true, context);
+    let Some(bounds) = intersect_bounds(&bounds, &context.bounds) else {
+        return;
+    };
+    let bounds = snap_bounds_to_pixels(&bounds);
 
-            let Some(swf) = swf_res.get_mut(swf.id()) else {
-                continue;
-            };
+    let layer = format!("{shape_depth_layer}_layer");
+    let Some(layer_image) =
+        allocate_layer_image(context, &layer, &bounds, display_object.swf_version())
+    else {
+        return;
+    };
 
-            // 处理循环播放逻辑
-            handle_animation_loop(&mut player, &mut root, swf);
+    let color_transform = context.transform_stack.transform().color_transform;
+    render_layer(
+        context,
+        &layer,
+        &bounds,
+        &layer_image,
+        color_transform,
+        None,
+        |context| {
+            if blend_mode == swf::BlendMode::Layer {
+                context.transparency_group = true;
+            }
+            render_display_object_content(
+                display_object,
+                context,
+                swf::BlendMode::Normal,
+                shape_depth_layer,
+            );
+        },
+    );
 
-            // 更新动画帧并触发帧事件
-            update_animation_frame(&mut commands, entity, &mut player, &mut root, swf);
+    render_layer_to_view(context, &layer_image, &bounds, blend_mode);
+}
 
-            let display_object_cache = display_object_entity_caches.entry(entity).or_default();
-            // 处理翻转缩放
-            let flip_x = &mut display_object_cache.flip_x;
-            let flip_y = &mut display_object_cache.flip_y;
-            let global_scale = global_transform.scale();
-            if transform.scale.x < 0.0 {
-                transform.scale.x = transform.scale.x.abs();
-                *flip_x = true;
-            }
-            if transform.scale.y < 0.0 {
-                transform.scale.y = transform.scale.y.abs();
-                *flip_y = true;
-            }
+/// 以复杂混合模式渲染显示对象。
+///
+/// 显示对象先渲染到离屏图层，当前上下文中已经绘制的内容复制一份到背景纹理中，两张纹理都只覆盖
+/// 显示对象的范围。离屏渲染图中将图层与背景混合，输出混合结果相对背景的差值，
+/// 以普通混合模式叠加到已经绘制的内容上即为混合结果。
+/// 背景只包含同一个 Flash 实体中先前绘制的内容，不包括场景中的其它实体。
+fn render_complex_blend(
+    display_object: &mut DisplayObject,
+    context: &mut RenderContext<'_>,
+    complex_blend: ComplexBlend,
+    shape_depth_layer: &str,
+) {
+    // Flash 中 Alpha 与 Erase 只在父级为 Layer 混合模式时生效，否则不绘制任何内容
+    if matches!(complex_blend, ComplexBlend::Alpha | ComplexBlend::Erase)
+        && !context.transparency_group
+    {
+        return;
+    }
 
-            let morph_shape_cache: &mut _ = &mut display_object_cache.morph_shape_frame_cache;
-            let image_cache = &mut display_object_cache.image_cache;
+    // 背景与图层都覆盖显示对象的范围，保证两者逐像素对齐
+    let matrix = context.transform_stack.transform().matrix;
+    let object_bounds = display_object.render_bounds_with_transform(&matrix, true, context);
+    let Some(bounds) = intersect_bounds(&object_bounds, &context.bounds) else {
+        return;
+    };
+    let bounds = snap_bounds_to_pixels(&bounds);
+    let layer = format!("{shape_depth_layer}_blend");
+    let backdrop_layer = format!("{shape_depth_layer}_backdrop");
+    let swf_version = display_object.swf_version();
+    let Some(layer_image) = allocate_layer_image(context, &layer, &bounds, swf_version) else {
+        return;
+    };
+    let Some(backdrop_image) = allocate_layer_image(context, &backdrop_layer, &bounds, swf_version)
+    else {
+        return;
+    };
 
-            // 处理DisplayList
-            let mut cache_draws = vec![];
-            let mut transform_stack = TransformStack::default();
-            // 创建渲染上下文
-            let mut context = RenderContext::new(
-                shapes.as_mut(),
-                meshes.as_mut(),
-                images.as_mut(),
-                gradients.as_mut(),
-                bitmaps.as_mut(),
-                morph_shape_cache,
-                &mut transform_stack,
-                image_cache,
-                &mut cache_draws,
-                &mut swf.shape_handles,
-                filter_texture_mesh.as_ref(),
-                &color_material.0,
-                global_scale,
-                *flip_x,
-                *flip_y,
-            );
-            process_display_list(
-                root.render_list_mut(),
-                &mut context,
+    let color_transform = context.transform_stack.transform().color_transform;
+    render_layer(
+        context,
+        &layer,
+        &bounds,
+        &layer_image,
+        color_transform,
+        None,
+        |context| {
+            render_display_object_content(
+                display_object,
+                context,
                 swf::BlendMode::Normal,
-                String::from("0"),
-                true,
+                shape_depth_layer,
             );
-            commands.entity(entity).insert(DrawShapes(context.commands));
+        },
+    );
 
-            // 处理离屏绘制
-            spawn_offscreen_texture(
-                &mut commands,
-                entity,
-                cache_draws,
-                &mut offscreen_textures,
-                display_object_cache,
-                global_scale,
-            );
+    // 已绘制的内容作为混合背景，原有的绘制命令保留，混合结果的差值叠加在它们之上
+    let mut backdrop_commands = context.commands.clone();
+    let offset = Matrix::translate(-bounds.x_min, -bounds.y_min);
+    for command in backdrop_commands.iter_mut() {
+        match command {
+            ShapeCommand::RenderShape { transform, .. }
+            | ShapeCommand::RenderBitmap { transform, .. } => {
+                transform.matrix = offset * transform.matrix;
+            }
         }
     }
-    display_object_entity_caches.retain(|entity, _| current_live_player.contains(entity));
+    context.cache_draws.push(ImageCacheDraw {
+        layer: backdrop_layer,
+        handle: backdrop_image.handle(),
+        clear_color: Color::NONE,
+        commands: backdrop_commands,
+        filters: vec![],
+        dirty: true,
+        size: backdrop_image.size(),
+        mask: None,
+        blend: Some((layer_image.handle(), complex_blend)),
+    });
+
+    render_layer_to_view(context, &backdrop_image, &bounds, swf::BlendMode::Normal);
 }
 
-/// 缓存信息
-struct CacheInfo {
-    image_info: ImageCacheInfo,
-    dirty: bool,
-    base_transform: SwfTransform,
-    bounds: Rectangle<Twips>,
-    draw_offset: IVec2,
-    filters: Vec<Filter>,
+/// 获取显示对象相对父级的变换，根显示列表需要处理翻转
+fn display_object_transform(
+    display_object: &DisplayObject,
+    context: &RenderContext<'_>,
+    is_root: bool,
+) -> SwfTransform {
+    let mut transform = *display_object.transform();
+    if is_root {
+        if context.flip_x {
+            transform.matrix.a = -transform.matrix.a;
+        }
+        if context.flip_y {
+            transform.matrix.d = -transform.matrix.d;
+        }
+    }
+    transform
 }
 
-/// 处理显示对象列表，遍历并渲染每个显示对象
-fn process_display_list(
-    display_list: ValuesMut<'_, u16, DisplayObject>,
+/// 渲染遮罩及其遮罩的兄弟对象。
+///
+/// 被遮罩的对象渲染到一个离屏图层，遮罩渲染到同样大小的另一张纹理，
+/// 离屏渲染图中将图层乘以遮罩的 alpha 后，再将图层作为位图绘制到当前上下文。
+fn render_masked(
+    mask: &mut DisplayObject,
+    maskees: &mut [&mut DisplayObject],
     context: &mut RenderContext<'_>,
     blend_mode: swf::BlendMode,
-    shape_depth_layer: String,
+    shape_depth_layer: &str,
     is_root: bool,
 ) {
-    // TODO:混合模式也有多个MC合成的情况，这里暂时没实现多MC合成的情况，暂时只实现单个图形的情况
-    // 实现方案：参考 Ruffle 中的处理方式，由多个Shape合成的MC上实现Blend模式需要渲染到一个OffscreenTexture中，
-    // 然后将OffscreenTexture渲染到屏幕上，这样做又需要使用OffscreenDrawCommand在渲染图中实现。
-    let blend_mode = if display_list.len() > 1 {
-        swf::BlendMode::Normal
-    } else {
-        blend_mode
+    let base_matrix = context.transform_stack.transform().matrix;
+    let mask_transform = display_object_transform(mask, context, is_root);
+    let mask_bounds =
+        mask.render_bounds_with_transform(&(base_matrix * mask_transform.matrix), false, context);
+
+    let mut maskee_bounds: Option<Rectangle<Twips>> = None;
+    for maskee in maskees.iter_mut().filter(|maskee| maskee.visible()) {
+        let matrix = base_matrix * display_object_transform(maskee, context, is_root).matrix;
+        let bounds = maskee.render_bounds_with_transform(&matrix, true, context);
+        maskee_bounds = Some(match maskee_bounds {
+            Some(maskee_bounds) => maskee_bounds.union(&bounds),
+            None => bounds,
+        });
+    }
+
+    // 被遮罩的对象只会在遮罩范围内可见
+    let Some(bounds) = maskee_bounds.and_then(|bounds| intersect_bounds(&bounds, &mask_bounds))
+    else {
+        return;
     };
-    for display_object in display_list {
-        let id = display_object.id();
-        let shape_depth_layer = format!("{}_{}_{}", shape_depth_layer, display_object.depth(), id);
-
-        let transform = if is_root {
-            let mut transform = *display_object.transform();
-            transform.matrix.a = if context.flip_x {
-                -transform.matrix.a
-            } else {
-                transform.matrix.a
-            };
-            transform.matrix.d = if context.flip_y {
-                -transform.matrix.d
-            } else {
-                transform.matrix.d
-            };
-            transform
-        } else {
-            *display_object.transform()
-        };
-        // 保存当前变换状态
-        context.transform_stack.push(&transform);
+    let bounds = snap_bounds_to_pixels(&bounds);
 
-        // 确定混合模式
-        let blend_mode = determine_blend_mode(blend_mode, display_object);
+    let layer = format!(
+        "{}_{}_{}_masked",
+        shape_depth_layer,
+        mask.depth(),
+        mask.id()
+    );
+    let mask_layer = format!("{}_{}_{}_mask", shape_depth_layer, mask.depth(), mask.id());
+    let swf_version = mask.swf_version();
+    let Some(layer_image) = allocate_layer_image(context, &layer, &bounds, swf_version) else {
+        return;
+    };
+    let Some(mask_image) = allocate_layer_image(context, &mask_layer, &bounds, swf_version) else {
+        return;
+    };
 
-        // 处理缓存和滤镜
-        let cache_info = process_cache_and_filters(display_object, context, id, &shape_depth_layer);
+    // 遮罩只关心覆盖范围，忽略颜色和透明度
+    render_layer(
+        context,
+        &mask_layer,
+        &bounds,
+        &mask_image,
+        ColorTransform::IDENTITY,
+        None,
+        |context| {
+            context.transform_stack.push(&SwfTransform {
+                matrix: mask_transform.matrix,
+                color_transform: ColorTransform {
+                    a_multiply: Fixed8::ZERO,
+                    a_add: 255,
+                    ..ColorTransform::IDENTITY
+                },
+            });
+            render_display_object(mask, context, swf::BlendMode::Normal, mask_layer.clone());
+            context.transform_stack.pop();
+        },
+    );
 
-        // 根据是否有缓存信息选择渲染方式
-        if let Some(cache_info) = cache_info {
-            render_with_cache(
-                display_object,
+    // 被遮罩的对象中也可能存在嵌套的遮罩
+    let color_transform = context.transform_stack.transform().color_transform;
+    render_layer(
+        context,
+        &layer,
+        &bounds,
+        &layer_image,
+        color_transform,
+        Some(mask_image.handle()),
+        |context| {
+            process_display_objects(
+                maskees,
                 context,
-                cache_info,
-                blend_mode,
-                &shape_depth_layer,
+                swf::BlendMode::Normal,
+                shape_depth_layer,
+                is_root,
             );
-        } else {
-            // 直接渲染显示对象
-            render_child(display_object, context, blend_mode, &shape_depth_layer);
-        }
+        },
+    );
+
+    render_layer_to_view(context, &layer_image, &bounds, blend_mode);
+}
+
+/// 计算两个矩形的交集，没有交集时返回 `None`
+fn intersect_bounds(a: &Rectangle<Twips>, b: &Rectangle<Twips>) -> Option<Rectangle<Twips>> {
+    let bounds = Rectangle {
+        x_min: a.x_min.max(b.x_min),
+        x_max: a.x_max.min(b.x_max),
+        y_min: a.y_min.max(b.y_min),
+        y_max: a.y_max.min(b.y_max),
+    };
+    (bounds.x_min < bounds.x_max && bounds.y_min < bounds.y_max).then_some(bounds)
+}
 
-        // TODO:处理复杂混合模式
+/// 将边界扩展到整像素，保证图层纹理与像素网格对齐
+fn snap_bounds_to_pixels(bounds: &Rectangle<Twips>) -> Rectangle<Twips> {
+    Rectangle {
+        x_min: Twips::from_pixels(bounds.x_min.to_pixels().floor()),
+        x_max: Twips::from_pixels(bounds.x_max.to_pixels().ceil()),
+        y_min: Twips::from_pixels(bounds.y_min.to_pixels().floor()),
+        y_max: Twips::from_pixels(bounds.y_max.to_pixels().ceil()),
+    }
+}
 
-        // 恢复变换状态
-        context.transform_stack.pop();
+/// 为离屏图层分配纹理，纹理按尺寸缓存在 `image_cache` 中
+fn allocate_layer_image(
+    context: &mut RenderContext<'_>,
+    layer: &str,
+    bounds: &Rectangle<Twips>,
+    swf_version: u8,
+) -> Option<ImageCacheInfo> {
+    let width = bounds.width().to_pixels().ceil().max(0.);
+    let height = bounds.height().to_pixels().ceil().max(0.);
+    if width > u16::MAX as f64 || height > u16::MAX as f64 {
+        warn_once!("图层大小超出限制, {layer}, ({width} x {height})");
+        return None;
     }
+    let actual_width = (width as f32 * context.scale.x) as u16;
+    let actual_height = (height as f32 * context.scale.y) as u16;
+
+    let cache = context.image_cache.entry(layer.to_owned()).or_default();
+    cache.update(
+        context.images,
+        &Matrix::IDENTITY,
+        width as u16,
+        height as u16,
+        actual_width,
+        actual_height,
+        swf_version,
+        IVec2::ZERO,
+    );
+    cache.image_info()
+}
+
+/// 将 `draw` 产生的绘制命令渲染到离屏图层，图层左上角对齐 `bounds`
+fn render_layer(
+    context: &mut RenderContext<'_>,
+    layer: &str,
+    bounds: &Rectangle<Twips>,
+    image_info: &ImageCacheInfo,
+    color_transform: ColorTransform,
+    mask: Option<Handle<Image>>,
+    draw: impl FnOnce(&mut RenderContext<'_>),
+) {
+    let base_matrix = context.transform_stack.transform().matrix;
+    let mut transform_stack = TransformStack::new();
+    transform_stack.push(&SwfTransform {
+        matrix: Matrix::translate(-bounds.x_min, -bounds.y_min) * base_matrix,
+        color_transform,
+    });
+
+    let mut layer_context = RenderContext::new(
+        context.shapes,
+        context.meshes,
+        context.images,
+        context.gradients,
+        context.bitmaps,
+        context.morph_shape_cache,
+        &mut transform_stack,
+        context.image_cache,
+        context.stroke_shape_cache,
+        context.bitmap_textures,
+        context.mesh_settings,
+        context.cache_draws,
+        context.shape_handles,
+        context.filter_texture_mesh,
+        context.color_material,
+        context.scale,
+        context.flip_x,
+        context.flip_y,
+    );
+    layer_context.bounds = Rectangle {
+        x_min: Twips::ZERO,
+        y_min: Twips::ZERO,
+        x_max: bounds.width(),
+        y_max: bounds.height(),
+    };
+    layer_context.transparency_group = context.transparency_group;
+    draw(&mut layer_context);
+
+    layer_context.cache_draws.push(ImageCacheDraw {
+        layer: layer.to_owned(),
+        handle: image_info.handle(),
+        clear_color: Color::NONE,
+        commands: layer_context.commands,
+        filters: vec![],
+        dirty: true,
+        size: image_info.size(),
+        mask,
+        blend: None,
+    });
+}
+
+/// 将离屏图层作为位图绘制到当前上下文
+fn render_layer_to_view(
+    context: &mut RenderContext<'_>,
+    image_info: &ImageCacheInfo,
+    bounds: &Rectangle<Twips>,
+    blend_mode: swf::BlendMode,
+) {
+    let scale = context.scale;
+    let bitmap_material = BitmapMaterial {
+        texture: image_info.handle(),
+        texture_transform: Mat4::IDENTITY,
+    };
+    context.commands.push(ShapeCommand::RenderBitmap {
+        mesh: context.filter_texture_mesh.0.clone(),
+        material: context.bitmaps.add(bitmap_material),
+        transform: SwfTransform {
+            matrix: Matrix {
+                a: image_info.size().x as f32 / scale.x,
+                d: image_info.size().y as f32 / scale.y,
+                tx: bounds.x_min,
+                ty: bounds.y_min,
+                ..Default::default()
+            },
+            color_transform: Default::default(),
+        },
+        blend_mode: BlendMode::from(blend_mode),
+    });
 }
 
 /// 确定要使用的混合模式
@@ -658,7 +2047,6 @@ fn render_with_cache(
             &cache_info,
             offset_x,
             offset_y,
-            blend_mode,
             shape_depth_layer,
         );
     }
@@ -674,7 +2062,6 @@ fn render_to_offscreen_texture(
     cache_info: &CacheInfo,
     offset_x: Twips,
     offset_y: Twips,
-    blend_mode: swf::BlendMode,
     shape_depth_layer: &str,
 ) {
     // 创建新的变换栈
@@ -698,6 +2085,9 @@ fn render_to_offscreen_texture(
         context.morph_shape_cache,
         &mut transform_stack,
         context.image_cache,
+        context.stroke_shape_cache,
+        context.bitmap_textures,
+        context.mesh_settings,
         context.cache_draws,
         context.shape_handles,
         context.filter_texture_mesh,
@@ -706,24 +2096,41 @@ fn render_to_offscreen_texture(
         false,
         false,
     );
+    let size = cache_info.image_info.size();
+    offscreen_context.bounds = Rectangle {
+        x_min: Twips::ZERO,
+        y_min: Twips::ZERO,
+        x_max: Twips::from_pixels(size.x as f64 / context.scale.x as f64),
+        y_max: Twips::from_pixels(size.y as f64 / context.scale.y as f64),
+    };
+    offscreen_context.transparency_group = context.transparency_group;
 
-    // 渲染显示对象到离屏上下文
+    // 渲染显示对象到离屏上下文，混合模式在绘制缓存位图时应用
     render_child(
         display_object,
         &mut offscreen_context,
-        blend_mode,
+        swf::BlendMode::Normal,
         shape_depth_layer,
     );
 
+    // 位图缓存的不透明背景色作为纹理的清除色
+    let clear_color = display_object
+        .opaque_background()
+        .map_or(Color::NONE, |color| {
+            Color::srgb_u8(color.r, color.g, color.b)
+        });
+
     // 将离屏上下文的绘制命令添加到缓存绘制列表
     offscreen_context.cache_draws.push(ImageCacheDraw {
         layer: shape_depth_layer.to_string(),
         handle: cache_info.image_info.handle(),
-        clear_color: Color::NONE,
+        clear_color,
         commands: offscreen_context.commands,
         filters: cache_info.filters.clone(),
         dirty: true,
         size: cache_info.image_info.size(),
+        mask: None,
+        blend: None,
     });
 }
 
@@ -769,15 +2176,7 @@ fn render_child(
     blend_mode: swf::BlendMode,
     shape_depth_layer: &str,
 ) {
-    if child.clip_depth() > 0 && child.allow_as_mask() {
-        warn_once!("Mass is not supported. TODO!");
-        // 作为遮罩处理
-        // 1. 标记为遮罩TODO:
-        // 2. 渲染
-        // 3. 标记遮罩活跃
-    } else {
-        render_display_object(child, context, blend_mode, shape_depth_layer.to_string());
-    }
+    render_display_object(child, context, blend_mode, shape_depth_layer.to_string());
 }
 
 fn render_display_object(
@@ -796,12 +2195,92 @@ fn render_display_object(
                 false,
             );
         }
+        DisplayObject::Button(button) => {
+            process_display_list(
+                button.render_list_mut(),
+                context,
+                blend_mode,
+                shape_depth_layer,
+                false,
+            );
+        }
         DisplayObject::Graphic(graphic) => {
             graphic.render_self(context, blend_mode);
         }
         DisplayObject::MorphShape(morph_shape) => {
             morph_shape.render_self(context, blend_mode);
         }
+        DisplayObject::Text(text) => {
+            text.render_self(context, blend_mode);
+        }
+        DisplayObject::EditText(edit_text) => {
+            edit_text.render_self(context, blend_mode);
+        }
+    }
+}
+
+/// 设备字体文本与图形分开绘制，显示在图形之上
+const DEVICE_TEXT_Z: f32 = 0.1;
+
+/// 为使用设备字体的动态文本更新 `Text2d` 子实体，多余的实体会被销毁
+fn spawn_device_texts(
+    commands: &mut Commands,
+    entity: Entity,
+    device_texts: Vec<DeviceText>,
+    device_text_entities: &mut Vec<Entity>,
+) {
+    let len = device_texts.len().min(device_text_entities.len());
+    for text_entity in device_text_entities.drain(len..) {
+        commands.entity(text_entity).despawn();
+    }
+    for (index, device_text) in device_texts.into_iter().enumerate() {
+        let matrix = device_text.matrix;
+        let color = device_text.color;
+        let justify = match device_text.align {
+            swf::TextAlign::Left | swf::TextAlign::Justify => Justify::Left,
+            swf::TextAlign::Center => Justify::Center,
+            swf::TextAlign::Right => Justify::Right,
+        };
+        let linebreak = if device_text.width.is_some() {
+            LineBreak::WordBoundary
+        } else {
+            LineBreak::NoWrap
+        };
+        let text = (
+            Text2d(device_text.text),
+            TextFont::from_font_size(device_text.font_size),
+            TextColor(Color::srgba_u8(color.r, color.g, color.b, color.a)),
+            TextLayout::new(justify, linebreak),
+            TextBounds {
+                width: device_text.width,
+                height: None,
+            },
+            Anchor::TOP_LEFT,
+            // Flash 坐标系 y 轴向下
+            Transform {
+                translation: Vec3::new(
+                    matrix.tx.to_pixels() as f32,
+                    -matrix.ty.to_pixels() as f32,
+                    DEVICE_TEXT_Z,
+                ),
+                rotation: Quat::from_rotation_z(-matrix.b.atan2(matrix.a)),
+                // 镜像（例如翻转的根影片）体现为 y 轴的负缩放，旋转只由 x 轴决定
+                scale: Vec3::new(
+                    (matrix.a * matrix.a + matrix.b * matrix.b).sqrt(),
+                    (matrix.c * matrix.c + matrix.d * matrix.d)
+                        .sqrt()
+                        .copysign(matrix.a * matrix.d - matrix.b * matrix.c),
+                    1.0,
+                ),
+            },
+        );
+        if let Some(text_entity) = device_text_entities.get(index) {
+            commands.entity(*text_entity).insert(text);
+        } else {
+            commands.entity(entity).with_children(|parent| {
+                device_text_entities.push(parent.spawn(text).id());
+            });
+        }
     }
 }
 
@@ -816,6 +2295,8 @@ fn spawn_offscreen_texture(
 ) {
     let layer_offscreen_cache = &mut display_object_cache.layer_offscreen_cache;
 
+    // 离屏纹理按照 order 顺序渲染，遮罩等依赖纹理需要先于使用它们的图层渲染，
+    // 所以每次更新都需要重新分配顺序
     let mut order = isize::MIN;
 
     for cache_draw in cache_draws.iter() {
@@ -823,6 +2304,7 @@ fn spawn_offscreen_texture(
         if !cache_draw.dirty {
             continue;
         }
+        order += 1;
 
         // 更新或创建离屏纹理实体
         if let Some(entity) = layer_offscreen_cache.get(&cache_draw.layer) {
@@ -832,17 +2314,20 @@ fn spawn_offscreen_texture(
 
             // 更新离屏纹理属性
             offscreen_texture.is_active = true;
+            offscreen_texture.order = order;
             offscreen_texture.target = cache_draw.handle.clone().into();
             offscreen_texture.size = cache_draw.size;
+            offscreen_texture.clear_color = cache_draw.clear_color;
             offscreen_texture.scale = scale;
             offscreen_texture.filters = cache_draw.filters.clone();
+            offscreen_texture.mask = cache_draw.mask.clone();
+            offscreen_texture.blend = cache_draw.blend.clone();
 
             // 更新绘制命令
             commands
                 .entity(*entity)
                 .insert(OffscreenDrawShapes(cache_draw.commands.clone()));
         } else {
-            order += 1;
             commands.entity(entity).with_children(|parent| {
                 let entity = parent
                     .spawn((
@@ -854,6 +2339,8 @@ fn spawn_offscreen_texture(
                             order,
                             filters: cache_draw.filters.clone(),
                             scale,
+                            mask: cache_draw.mask.clone(),
+                            blend: cache_draw.blend.clone(),
                         },
                         OffscreenDrawShapes(cache_draw.commands.clone()),
                     ))
@@ -864,3 +2351,126 @@ fn spawn_offscreen_texture(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{
+        system::RunSystemOnce,
+        world::{CommandQueue, World},
+    };
+
+    use super::*;
+    use crate::player::PlaybackDirection;
+
+    /// 根影片共 10 帧，`walk` 为第 5-9 帧
+    fn swf() -> Swf {
+        Swf::from_labels(10, &[(1, "anim_idle"), (5, "anim_walk"), (9, "end_walk")])
+    }
+
+    /// 从动画开始逐帧推进，返回根影片依次显示的帧，播放完毕时提前结束
+    fn frame_order(player: FlashPlayer, steps: usize) -> Vec<FrameNumber> {
+        let swf = swf();
+        let world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let mut player = player;
+        let mut root = McRoot(MovieClip::new(swf.swf_movie.clone()));
+        player.start_animation(&swf, &mut root);
+        let mut frames = vec![root.current_frame()];
+        for _ in 0..steps {
+            if !advance_frame(
+                &mut commands,
+                Entity::PLACEHOLDER,
+                &mut player,
+                &mut root,
+                &swf,
+            ) {
+                break;
+            }
+            frames.push(root.current_frame());
+        }
+        frames
+    }
+
+    #[test]
+    fn forward_playback_stops_on_the_last_frame() {
+        let player = FlashPlayer::from_animation_name("walk");
+        assert_eq!(frame_order(player, 10), [5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn reverse_playback_starts_from_the_last_frame() {
+        let player =
+            FlashPlayer::from_animation_name("walk").with_direction(PlaybackDirection::Reverse);
+        assert_eq!(frame_order(player, 10), [9, 8, 7, 6, 5]);
+    }
+
+    #[test]
+    fn ping_pong_playback_turns_around_on_the_last_frame() {
+        let player =
+            FlashPlayer::from_animation_name("walk").with_direction(PlaybackDirection::PingPong);
+        assert_eq!(frame_order(player, 10), [5, 6, 7, 8, 9, 8, 7, 6, 5]);
+
+        let player = FlashPlayer::from_animation_name("walk")
+            .with_direction(PlaybackDirection::PingPong)
+            .with_looping(true);
+        assert_eq!(frame_order(player, 10), [5, 6, 7, 8, 9, 8, 7, 6, 5, 6, 7]);
+    }
+
+    fn spawn_player(world: &mut World, swf: &Handle<Swf>, player: FlashPlayer) -> Entity {
+        let mut player = player;
+        let root = {
+            let swf = world.resource::<Assets<Swf>>().get(swf).unwrap();
+            let mut root = McRoot(MovieClip::new(swf.swf_movie.clone()));
+            player.start_animation(swf, &mut root);
+            root
+        };
+        world.spawn((Flash(swf.clone()), player, root)).id()
+    }
+
+    fn playback(world: &World, entity: Entity) -> (Option<String>, FrameNumber) {
+        let player = world.get::<FlashPlayer>(entity).unwrap();
+        (
+            player.current_animation().map(ToOwned::to_owned),
+            player.current_frame(),
+        )
+    }
+
+    #[test]
+    fn followers_only_change_when_the_leader_moves() {
+        let mut world = World::new();
+        let mut assets = Assets::<Swf>::default();
+        let swf = assets.add(swf());
+        world.insert_resource(assets);
+        world.insert_resource(Time::<()>::default());
+        world.insert_resource(FlashAdvanceMode::Fixed(2));
+        let leader = spawn_player(&mut world, &swf, FlashPlayer::from_animation_name("walk"));
+        let follower = spawn_player(&mut world, &swf, FlashPlayer::from_animation_name("idle"));
+        world
+            .entity_mut(follower)
+            .insert(FlashSyncGroup::new(leader));
+
+        world.run_system_once(advance_animation).unwrap();
+        assert_eq!(playback(&world, leader), (Some("walk".into()), 3));
+        assert_eq!(playback(&world, follower), playback(&world, leader));
+        assert_eq!(world.get::<McRoot>(follower).unwrap().current_frame(), 7);
+
+        // 领导者没有推进时，跟随者的根影片不会被标记为修改
+        let last_changed = |world: &World| {
+            world
+                .entity(follower)
+                .get_ref::<McRoot>()
+                .unwrap()
+                .last_changed()
+        };
+        let changed = last_changed(&world);
+        world.insert_resource(FlashAdvanceMode::Fixed(0));
+        world.run_system_once(advance_animation).unwrap();
+        assert_eq!(last_changed(&world), changed);
+
+        world.insert_resource(FlashAdvanceMode::Fixed(1));
+        world.run_system_once(advance_animation).unwrap();
+        assert_ne!(last_changed(&world), changed);
+        assert_eq!(playback(&world, follower), (Some("walk".into()), 4));
+    }
+}
@@ -1,13 +1,99 @@
-use crate::{assets::Swf, swf_runtime::movie_clip::MovieClip};
+use crate::{
+    assets::Swf,
+    swf_runtime::{movie_clip::MovieClip, sound::StartSound},
+};
 use bevy::{
     asset::{AsAssetId, AssetId, Handle},
+    ecs::entity::Entity,
     log::error,
     prelude::{
-        Component, Deref, DerefMut, ReflectComponent, ReflectDefault, Transform, Visibility,
+        Component, Deref, DerefMut, ReflectComponent, ReflectDefault, ReflectResource, Resource,
+        Transform, Visibility,
     },
     reflect::Reflect,
     time::{Timer, TimerMode},
 };
+use std::{collections::VecDeque, time::Duration};
+
+/// 播放方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default, Debug)]
+pub enum PlaybackDirection {
+    /// 正向播放
+    #[default]
+    Forward,
+    /// 倒放，从动画的最后一帧播放到第一帧
+    Reverse,
+    /// 往返播放，正向播放到最后一帧后倒放回第一帧
+    PingPong,
+}
+
+/// 从当前动画切换到队列中下一个动画的时机
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default, Debug)]
+pub enum Transition {
+    /// 当前动画播放完毕后切换，循环动画在当前这一轮结束时切换
+    #[default]
+    AtEnd,
+    /// 在当前动画的下一个帧事件处切换，没有帧事件时在播放完毕后切换
+    OnFrameEvent,
+    /// 下一帧立即切换
+    Immediate,
+}
+
+/// 队列中等待播放的动画
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(Debug)]
+pub struct QueuedAnimation {
+    name: String,
+    looping: bool,
+    transition: Transition,
+}
+
+impl QueuedAnimation {
+    /// 播放一次的动画
+    pub fn once(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            looping: false,
+            transition: Transition::AtEnd,
+        }
+    }
+
+    /// 循环播放的动画
+    pub fn looping(name: impl Into<String>) -> Self {
+        Self {
+            looping: true,
+            ..Self::once(name)
+        }
+    }
+
+    /// 设置从上一个动画切换到该动画的时机
+    pub fn with_transition(mut self, transition: Transition) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn transition(&self) -> Transition {
+        self.transition
+    }
+}
+
+/// 动画生命周期事件，在推进动画时转换为对应的实体事件触发
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AnimationEvent {
+    Started(Option<String>),
+    Looped(Option<String>, u32),
+    Interrupted(Option<String>),
+}
 
 /// Flash 播放器组件模块，定义了与 Flash 动画播放相关的组件和逻辑。
 #[derive(Component, Debug, Clone, Reflect)]
@@ -17,10 +103,42 @@ pub struct FlashPlayer {
     looping: bool,
     current_animation: Option<String>,
     speed: f32,
+    /// 当前动画在动画描述文件中定义的默认播放速度，与 `speed` 相乘
+    animation_speed: f32,
+    /// 覆盖 SWF 头中的帧率，为 `None` 时使用 SWF 文件自身的帧率
+    frame_rate: Option<f32>,
     total_frames: u16,
     current_frame: u16,
+    /// 当前动画在根影片中的起始帧
+    start_frame: u16,
+    /// 播放方向
+    direction: PlaybackDirection,
+    /// 当前是否正在倒放，往返播放时会在到达最后一帧后切换
+    reversing: bool,
+    /// 是否暂停，暂停时不推进动画
+    paused: bool,
+    /// 跳转、单步或切换皮肤后需要重新绘制当前帧（暂停时也需要）
+    redraw: bool,
     /// 是否完成，用于标记触发一次触发完成事件
     completed: bool,
+    /// 等待播放的动画队列
+    queue: VecDeque<QueuedAnimation>,
+    /// 非循环动画播放完毕且队列为空时循环播放的默认动画
+    fallback: Option<String>,
+    /// 当前动画已经完成的循环次数
+    loop_count: u32,
+    /// 等待触发的动画生命周期事件
+    #[reflect(ignore)]
+    animation_events: Vec<AnimationEvent>,
+    /// 当前选择的皮肤，对应子影片中的 `skin_<name>` 帧标签
+    skin: Option<String>,
+    /// 已经应用到影片上的皮肤，与 `skin` 不同时需要重新应用并触发皮肤切换事件
+    applied_skin: Option<String>,
+    /// 是否播放时间轴上的声音，关闭时仍然会触发声音事件
+    sounds_enabled: bool,
+    /// 推进时遇到的声音及其所在子影片的实例路径
+    #[reflect(ignore)]
+    pending_sounds: Vec<(String, StartSound)>,
 }
 
 impl FlashPlayer {
@@ -68,8 +186,50 @@ impl FlashPlayer {
         self
     }
 
+    /// 使用指定的帧率播放，而不是 SWF 头中定义的帧率
+    pub fn with_frame_rate(mut self, frame_rate: f32) -> Self {
+        self.set_frame_rate(frame_rate);
+        self
+    }
+
+    /// 使用指定的皮肤
+    pub fn with_skin(mut self, skin: impl Into<String>) -> Self {
+        self.skin = Some(skin.into());
+        self
+    }
+
+    /// 将动画加入播放队列
+    pub fn with_queued(mut self, animation: QueuedAnimation) -> Self {
+        self.queue(animation);
+        self
+    }
+
+    /// 使用默认动画，非循环动画播放完毕且队列为空时循环播放该动画
+    pub fn with_fallback(mut self, animation_name: impl Into<String>) -> Self {
+        self.fallback = Some(animation_name.into());
+        self
+    }
+
+    /// 使用指定的播放方向
+    pub fn with_direction(mut self, direction: PlaybackDirection) -> Self {
+        self.set_direction(direction);
+        self
+    }
+
+    /// 是否播放时间轴上的声音，关闭时只触发 `FlashSoundEvent`，由使用者自行播放
+    pub fn with_sounds(mut self, enabled: bool) -> Self {
+        self.sounds_enabled = enabled;
+        self
+    }
+
+    /// 回到当前动画的起始位置，倒放时为动画的最后一帧
     pub fn reset(&mut self) {
-        self.current_frame = 1;
+        self.reversing = self.direction == PlaybackDirection::Reverse;
+        self.current_frame = if self.reversing {
+            self.total_frames.max(1)
+        } else {
+            1
+        };
         self.completed = false;
     }
 
@@ -93,8 +253,47 @@ impl FlashPlayer {
         self.looping = looping;
     }
 
+    /// 当前动画是否播放完毕，往返播放时需要倒放回第一帧才算完成
     pub fn is_completed(&mut self) -> bool {
-        self.current_frame >= self.total_frames
+        match self.direction {
+            PlaybackDirection::Forward => self.current_frame >= self.total_frames,
+            PlaybackDirection::Reverse => self.current_frame <= 1,
+            PlaybackDirection::PingPong => self.reversing && self.current_frame <= 1,
+        }
+    }
+
+    pub fn direction(&self) -> PlaybackDirection {
+        self.direction
+    }
+
+    /// 设置播放方向，从当前帧开始按新的方向播放
+    pub fn set_direction(&mut self, direction: PlaybackDirection) {
+        self.direction = direction;
+        match direction {
+            PlaybackDirection::Forward => self.reversing = false,
+            PlaybackDirection::Reverse => self.reversing = true,
+            PlaybackDirection::PingPong => {}
+        }
+    }
+
+    /// 当前是否正在倒放
+    pub fn is_reversing(&self) -> bool {
+        self.reversing
+    }
+
+    /// 往返播放到达最后一帧时切换为倒放
+    pub(crate) fn turn_around(&mut self) {
+        if self.direction == PlaybackDirection::PingPong
+            && !self.reversing
+            && self.current_frame >= self.total_frames
+        {
+            self.reversing = true;
+        }
+    }
+
+    /// 当前帧在根影片中对应的帧
+    pub(crate) fn root_frame(&self) -> u16 {
+        self.start_frame + self.current_frame.max(1) - 1
     }
 
     fn set_total_frames(&mut self, total_frames: u16) {
@@ -113,30 +312,309 @@ impl FlashPlayer {
         self.current_frame += 1;
     }
 
+    pub fn decr_frame(&mut self) {
+        self.current_frame = self.current_frame.saturating_sub(1);
+    }
+
+    /// 播放指定的动画并清空播放队列，动画描述文件中定义了默认播放参数时会应用这些参数。
+    ///
+    /// 当前动画尚未播放完毕时会触发动画中断事件
     pub fn set_play(&mut self, name: &str, swf: &Swf, root: &mut McRoot) {
+        self.queue.clear();
+        self.switch_animation(name, swf, root);
+    }
+
+    /// 切换到指定的动画，当前动画尚未播放完毕时记录动画中断事件
+    fn switch_animation(&mut self, name: &str, swf: &Swf, root: &mut McRoot) {
+        if !self.completed && !self.is_completed() {
+            self.animation_events
+                .push(AnimationEvent::Interrupted(self.current_animation.clone()));
+        }
         self.current_animation = Some(name.to_owned());
+        self.start_animation(swf, root);
+        self.redraw = true;
+    }
+
+    /// 从头开始播放当前动画并记录动画开始事件
+    pub(crate) fn start_animation(&mut self, swf: &Swf, root: &mut McRoot) {
+        self.apply_animation_defaults(swf);
+        self.play_target_animation(swf, root);
+        self.loop_count = 0;
+        self.animation_events
+            .push(AnimationEvent::Started(self.current_animation.clone()));
+    }
+
+    /// 循环动画完成一轮，跳回当前动画第一帧并记录动画循环事件
+    pub(crate) fn loop_animation(&mut self, swf: &Swf, root: &mut McRoot) {
         self.play_target_animation(swf, root);
+        self.loop_count += 1;
+        self.animation_events.push(AnimationEvent::Looped(
+            self.current_animation.clone(),
+            self.loop_count,
+        ));
+    }
+
+    /// 当前动画已经完成的循环次数
+    pub fn loop_count(&self) -> u32 {
+        self.loop_count
+    }
+
+    /// 取出等待触发的动画生命周期事件
+    pub(crate) fn take_animation_events(&mut self) -> Vec<AnimationEvent> {
+        std::mem::take(&mut self.animation_events)
+    }
+
+    pub fn sounds_enabled(&self) -> bool {
+        self.sounds_enabled
+    }
+
+    pub fn set_sounds_enabled(&mut self, enabled: bool) {
+        self.sounds_enabled = enabled;
+    }
+
+    /// 记录推进时遇到的声音
+    pub(crate) fn push_sounds(&mut self, sounds: Vec<(String, StartSound)>) {
+        self.pending_sounds.extend(sounds);
+    }
+
+    /// 取出等待播放的声音
+    pub(crate) fn take_sounds(&mut self) -> Vec<(String, StartSound)> {
+        std::mem::take(&mut self.pending_sounds)
+    }
+
+    /// 应用当前动画的默认播放参数
+    pub(crate) fn apply_animation_defaults(&mut self, swf: &Swf) {
+        let defaults = self
+            .current_animation
+            .as_deref()
+            .and_then(|name| swf.animation_defaults().get(name))
+            .copied()
+            .unwrap_or_default();
+        if let Some(looping) = defaults.looping {
+            self.looping = looping;
+        }
+        self.animation_speed = match defaults.speed {
+            Some(speed) if speed > 0.0 => speed,
+            Some(_) => {
+                error!("Speed must be greater than 0.0");
+                1.0
+            }
+            None => 1.0,
+        };
+    }
+
+    /// 将动画加入播放队列，按照动画的切换时机在当前动画之后播放
+    pub fn queue(&mut self, animation: QueuedAnimation) {
+        self.queue.push_back(animation);
+    }
+
+    /// 清空播放队列
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+    }
+
+    /// 等待播放的动画
+    pub fn queued(&self) -> impl Iterator<Item = &QueuedAnimation> {
+        self.queue.iter()
+    }
+
+    /// 默认动画
+    pub fn fallback(&self) -> Option<&str> {
+        self.fallback.as_deref()
+    }
+
+    /// 设置默认动画，非循环动画播放完毕且队列为空时循环播放该动画
+    pub fn set_fallback(&mut self, animation_name: impl Into<String>) {
+        self.fallback = Some(animation_name.into());
+    }
+
+    /// 取消默认动画
+    pub fn clear_fallback(&mut self) {
+        self.fallback = None;
+    }
+
+    /// 取出下一个到达切换时机的动画。
+    ///
+    /// `at_end` 表示当前动画已经播放完毕（或循环动画完成一轮），
+    /// `on_frame_event` 表示当前帧触发了帧事件
+    pub(crate) fn next_queued(
+        &mut self,
+        at_end: bool,
+        on_frame_event: bool,
+    ) -> Option<QueuedAnimation> {
+        if let Some(next) = self.queue.front() {
+            let ready = match next.transition {
+                Transition::AtEnd => at_end,
+                Transition::OnFrameEvent => at_end || on_frame_event,
+                Transition::Immediate => true,
+            };
+            return if ready { self.queue.pop_front() } else { None };
+        }
+        match &self.fallback {
+            Some(fallback)
+                if at_end && !self.looping && self.current_animation.as_ref() != Some(fallback) =>
+            {
+                Some(QueuedAnimation::looping(fallback.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// 播放队列中取出的动画
+    pub(crate) fn play_queued(&mut self, animation: QueuedAnimation, swf: &Swf, root: &mut McRoot) {
+        self.switch_animation(&animation.name, swf, root);
+        self.looping = animation.looping;
+    }
+
+    /// 当前选择的皮肤
+    pub fn skin(&self) -> Option<&str> {
+        self.skin.as_deref()
+    }
+
+    /// 切换皮肤，所有定义了 `skin_<name>` 帧标签的子影片会跳转并停在该帧，
+    /// 下一次推进动画时生效
+    pub fn set_skin(&mut self, skin: impl Into<String>) {
+        self.skin = Some(skin.into());
+        self.redraw = true;
+    }
+
+    /// 取消皮肤，被皮肤停住的子影片从第一帧重新播放
+    pub fn clear_skin(&mut self) {
+        self.skin = None;
+        self.redraw = true;
+    }
+
+    /// 皮肤发生变化时记录为已应用，并返回之前应用的皮肤
+    pub(crate) fn take_skin_change(&mut self) -> Option<Option<String>> {
+        if self.skin == self.applied_skin {
+            return None;
+        }
+        Some(std::mem::replace(&mut self.applied_skin, self.skin.clone()))
+    }
+
+    /// 暂停播放，当前帧保持不变
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// 从当前帧继续播放
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 跳转到当前动画中的第 `frame` 帧（从 1 开始），超出范围时限制在动画范围内
+    pub fn seek(&mut self, frame: u16, swf: &Swf, root: &mut McRoot) {
+        let frame = frame.clamp(1, self.total_frames.max(1));
+        root.goto_frame(swf.characters(), self.start_frame + frame - 1, false);
+        self.current_frame = frame;
+        self.completed = false;
+        self.redraw = true;
+    }
+
+    /// 跳转到当前动画范围内的帧标签
+    pub fn seek_to_label(&mut self, label: &str, swf: &Swf, root: &mut McRoot) {
+        let Some(&frame) = swf.frame_labels().get(label) else {
+            error!("Frame label '{}' not found", label);
+            return;
+        };
+        let end_frame = self.start_frame + self.total_frames.max(1) - 1;
+        if !(self.start_frame..=end_frame).contains(&frame) {
+            error!(
+                "Frame label '{}' is outside of the current animation",
+                label
+            );
+            return;
+        }
+        self.seek(frame - self.start_frame + 1, swf, root);
+    }
+
+    /// 前进一帧，循环播放时在最后一帧后回到第一帧
+    pub fn step_forward(&mut self, swf: &Swf, root: &mut McRoot) {
+        let frame = if self.current_frame >= self.total_frames && self.looping {
+            1
+        } else {
+            self.current_frame.saturating_add(1)
+        };
+        self.seek(frame, swf, root);
+    }
+
+    /// 后退一帧，循环播放时在第一帧前回到最后一帧
+    pub fn step_backward(&mut self, swf: &Swf, root: &mut McRoot) {
+        let frame = if self.current_frame <= 1 && self.looping {
+            self.total_frames
+        } else {
+            self.current_frame.saturating_sub(1)
+        };
+        self.seek(frame, swf, root);
+    }
+
+    /// 标记需要重新绘制当前帧
+    pub(crate) fn request_redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    /// 取出重新绘制标记
+    pub(crate) fn take_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.redraw)
     }
 
     pub fn speed(&self) -> f32 {
         self.speed
     }
 
+    /// 实际播放速度，包含动画描述文件中定义的默认速度
+    pub(crate) fn effective_speed(&self) -> f32 {
+        self.speed * self.animation_speed
+    }
+
+    /// 覆盖的帧率，为 `None` 时使用 SWF 头中的帧率
+    pub fn frame_rate(&self) -> Option<f32> {
+        self.frame_rate
+    }
+
+    /// 覆盖 SWF 头中的帧率，`speed` 仍然会作为倍率作用在该帧率上
+    pub fn set_frame_rate(&mut self, frame_rate: f32) {
+        if frame_rate <= 0.0 {
+            error!("Frame rate must be greater than 0.0");
+            return;
+        }
+        self.frame_rate = Some(frame_rate);
+    }
+
+    /// 取消帧率覆盖，恢复使用 SWF 头中的帧率
+    pub fn reset_frame_rate(&mut self) {
+        self.frame_rate = None;
+    }
+
+    /// 实际播放使用的帧率，未覆盖时使用 SWF 头中的帧率
+    pub(crate) fn effective_frame_rate(&self, swf: &Swf) -> f32 {
+        self.frame_rate.unwrap_or_else(|| swf.frame_rate())
+    }
+
     pub(crate) fn play_target_animation(&mut self, swf: &Swf, root: &mut McRoot) {
         if let Some(name) = &self.current_animation {
             match swf.animations().get(name.as_str()) {
                 Some((frame, total_frames)) => {
-                    root.goto_frame(swf.characters(), *frame, false);
-                    self.reset();
+                    self.start_frame = *frame;
                     self.set_total_frames(*total_frames);
+                    self.reset();
+                    root.goto_frame(swf.characters(), self.root_frame(), false);
                 }
                 None => {
                     error!("Animation '{}' not found", name);
                 }
             }
         } else {
-            self.reset();
+            self.start_frame = 1;
             self.total_frames = root.total_frames();
+            self.reset();
+            if self.reversing {
+                root.goto_frame(swf.characters(), self.root_frame(), false);
+            }
         }
     }
 }
@@ -147,12 +625,50 @@ impl Default for FlashPlayer {
             looping: false,
             current_animation: None,
             speed: 1.0,
+            animation_speed: 1.0,
+            frame_rate: None,
             total_frames: 0,
             current_frame: 0,
+            start_frame: 1,
+            direction: PlaybackDirection::Forward,
+            reversing: false,
+            paused: false,
+            redraw: false,
             completed: false,
+            loop_count: 0,
+            animation_events: Vec::new(),
+            queue: VecDeque::new(),
+            fallback: None,
+            skin: None,
+            applied_skin: None,
+            sounds_enabled: true,
+            pending_sounds: Vec::new(),
         }
     }
 }
+/// 同步组，添加到跟随者实体上，使其镜像领导者的当前动画、帧与暂停状态。
+///
+/// 用于由多个 SWF 组成的角色（身体、武器、特效等），跟随者不再按自身的定时器推进，
+/// 而是在领导者推进的同一次更新中逐帧跟随，子影片照常播放并触发帧事件。
+/// 跟随者的 SWF 中没有领导者的动画时保持当前动画，帧超出范围时限制在当前动画范围内。
+/// 领导者也可以是其它同步组的跟随者，成环的同步组会被忽略
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Debug)]
+pub struct FlashSyncGroup {
+    leader: Entity,
+}
+
+impl FlashSyncGroup {
+    pub fn new(leader: Entity) -> Self {
+        Self { leader }
+    }
+
+    /// 领导者实体
+    pub fn leader(&self) -> Entity {
+        self.leader
+    }
+}
+
 /// Flash 动画中的根 影片 需要通过它来控制动画的播放
 #[derive(Debug, Clone, Component, DerefMut, Deref)]
 pub struct McRoot(pub MovieClip);
@@ -170,13 +686,271 @@ impl AsAssetId for Flash {
     }
 }
 
-/// Flash动画都默认设置为30FPS
+/// SWF 未加载完成前使用的默认帧率
+const DEFAULT_FRAME_RATE: f32 = 30.0;
+
+/// Flash 动画的推进方式
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource, Default, Debug)]
+pub enum FlashAdvanceMode {
+    /// 按经过的时间推进，一次更新推进经过的所有帧，帧率较低时会跳帧而不是变慢
+    #[default]
+    Time,
+    /// 在 `FixedUpdate` 中每次推进固定帧数并触发帧事件，不受帧率覆盖与播放速度影响，用于帧同步与回放
+    Fixed(u32),
+}
+
+/// Flash 动画定时器，SWF 加载完成后会使用 SWF 头中的帧率
 #[derive(Component, Debug, Clone, Deref, DerefMut)]
-pub struct FlashPlayerTimer(Timer);
+pub struct FlashPlayerTimer {
+    #[deref]
+    timer: Timer,
+}
+
+impl FlashPlayerTimer {
+    pub fn from_frame_rate(frame_rate: f32) -> Self {
+        let mut timer = Self::default();
+        timer.set_frame_rate(frame_rate);
+        timer
+    }
+
+    /// 当前定时器对应的帧率
+    pub fn frame_rate(&self) -> f32 {
+        1.0 / self.timer.duration().as_secs_f32()
+    }
+
+    /// 设置定时器的帧率，帧率无效时（例如 SWF 头中帧率为 0）回退到默认帧率
+    pub fn set_frame_rate(&mut self, frame_rate: f32) {
+        let frame_rate = if frame_rate > 0.0 {
+            frame_rate
+        } else {
+            DEFAULT_FRAME_RATE
+        };
+        let duration = Duration::from_secs_f32(1.0 / frame_rate);
+        if self.timer.duration() != duration {
+            self.timer.set_duration(duration);
+        }
+    }
+
+    /// 按经过的时间推进定时器，返回这段时间内需要推进的帧数
+    pub(crate) fn tick_frames(&mut self, delta: Duration) -> u32 {
+        self.timer.tick(delta).times_finished_this_tick()
+    }
+}
 
 impl Default for FlashPlayerTimer {
     /// 动画定时器，默认30fps
     fn default() -> Self {
-        Self(Timer::from_seconds(1. / 30., TimerMode::Repeating))
+        Self {
+            timer: Timer::from_seconds(1. / DEFAULT_FRAME_RATE, TimerMode::Repeating),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 根影片共 10 帧：`idle` 为第 1-4 帧，`walk` 为第 5-9 帧，第 7 帧有帧事件 `step`
+    fn swf() -> Swf {
+        Swf::from_labels(
+            10,
+            &[
+                (1, "anim_idle"),
+                (5, "anim_walk"),
+                (7, "event_step"),
+                (9, "end_walk"),
+            ],
+        )
+    }
+
+    fn play(player: FlashPlayer) -> (FlashPlayer, McRoot, Swf) {
+        let swf = swf();
+        let mut player = player;
+        let mut root = McRoot(MovieClip::new(swf.swf_movie.clone()));
+        player.start_animation(&swf, &mut root);
+        player.take_animation_events();
+        (player, root, swf)
+    }
+
+    #[test]
+    fn seek_clamps_to_animation_range() {
+        let (mut player, mut root, swf) = play(FlashPlayer::from_animation_name("walk"));
+        assert_eq!(player.total_frames(), 5);
+
+        player.seek(0, &swf, &mut root);
+        assert_eq!(player.current_frame(), 1);
+        assert_eq!(root.current_frame(), 5);
+
+        player.seek(99, &swf, &mut root);
+        assert_eq!(player.current_frame(), 5);
+        assert_eq!(root.current_frame(), 9);
+    }
+
+    #[test]
+    fn seek_to_label_ignores_unknown_and_outside_labels() {
+        let (mut player, mut root, swf) = play(FlashPlayer::from_animation_name("walk"));
+
+        player.seek_to_label("event_step", &swf, &mut root);
+        assert_eq!(player.current_frame(), 3);
+        assert_eq!(root.current_frame(), 7);
+
+        player.seek_to_label("missing", &swf, &mut root);
+        assert_eq!(player.current_frame(), 3);
+
+        player.seek_to_label("anim_idle", &swf, &mut root);
+        assert_eq!(player.current_frame(), 3);
+        assert_eq!(root.current_frame(), 7);
+    }
+
+    #[test]
+    fn step_while_paused() {
+        let (mut player, mut root, swf) = play(FlashPlayer::from_animation_name("walk"));
+        player.pause();
+        player.take_redraw();
+
+        player.step_forward(&swf, &mut root);
+        assert_eq!(player.current_frame(), 2);
+        assert_eq!(root.current_frame(), 6);
+        assert!(player.is_paused());
+        assert!(player.take_redraw());
+
+        player.step_backward(&swf, &mut root);
+        player.step_backward(&swf, &mut root);
+        assert_eq!(player.current_frame(), 1);
+
+        player.seek(5, &swf, &mut root);
+        player.step_forward(&swf, &mut root);
+        assert_eq!(player.current_frame(), 5);
+
+        player.set_looping(true);
+        player.step_forward(&swf, &mut root);
+        assert_eq!(player.current_frame(), 1);
+        player.step_backward(&swf, &mut root);
+        assert_eq!(player.current_frame(), 5);
+        assert_eq!(root.current_frame(), 9);
+        assert!(player.is_paused());
+    }
+
+    #[test]
+    fn queued_animations_play_in_order_at_their_transitions() {
+        let (mut player, mut root, swf) = play(
+            FlashPlayer::from_animation_name("idle")
+                .with_queued(QueuedAnimation::once("walk"))
+                .with_queued(
+                    QueuedAnimation::looping("idle").with_transition(Transition::OnFrameEvent),
+                )
+                .with_queued(QueuedAnimation::once("walk").with_transition(Transition::Immediate)),
+        );
+        assert!(player.next_queued(false, true).is_none());
+        let next = player.next_queued(true, false).unwrap();
+        assert_eq!(next.name(), "walk");
+        player.play_queued(next, &swf, &mut root);
+        assert_eq!(player.current_animation(), Some("walk"));
+        assert_eq!(player.queued().count(), 2);
+
+        assert!(player.next_queued(false, false).is_none());
+        let next = player.next_queued(false, true).unwrap();
+        assert_eq!(next.name(), "idle");
+        player.play_queued(next, &swf, &mut root);
+        assert!(player.is_looping());
+
+        let next = player.next_queued(false, false).unwrap();
+        assert_eq!(next.name(), "walk");
+        assert!(!next.is_looping());
+        assert_eq!(player.queued().count(), 0);
+    }
+
+    #[test]
+    fn fallback_plays_once_the_queue_is_empty() {
+        let (mut player, mut root, swf) = play(
+            FlashPlayer::from_animation_name("walk")
+                .with_queued(QueuedAnimation::once("walk"))
+                .with_fallback("idle"),
+        );
+        let next = player.next_queued(true, false).unwrap();
+        assert_eq!(next.name(), "walk");
+        player.play_queued(next, &swf, &mut root);
+
+        assert!(player.next_queued(false, true).is_none());
+        let fallback = player.next_queued(true, false).unwrap();
+        assert_eq!(fallback.name(), "idle");
+        assert!(fallback.is_looping());
+        player.play_queued(fallback, &swf, &mut root);
+
+        // 默认动画自身循环播放，不会再次切换
+        assert!(player.next_queued(true, false).is_none());
+    }
+
+    #[test]
+    fn set_play_clears_the_queue() {
+        let (mut player, mut root, swf) = play(
+            FlashPlayer::from_animation_name("idle")
+                .with_queued(QueuedAnimation::once("walk"))
+                .with_fallback("idle"),
+        );
+        player.set_play("walk", &swf, &mut root);
+        assert_eq!(player.queued().count(), 0);
+        assert_eq!(player.current_animation(), Some("walk"));
+        // 默认动画不受影响
+        assert_eq!(player.fallback(), Some("idle"));
+    }
+
+    #[test]
+    fn replacing_an_unfinished_animation_interrupts_it() {
+        let (mut player, mut root, swf) = play(FlashPlayer::from_animation_name("idle"));
+        player.set_play("walk", &swf, &mut root);
+        assert_eq!(
+            player.take_animation_events(),
+            [
+                AnimationEvent::Interrupted(Some("idle".into())),
+                AnimationEvent::Started(Some("walk".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn finished_animations_are_not_interrupted() {
+        let (mut player, mut root, swf) = play(FlashPlayer::from_animation_name("idle"));
+        player.seek(4, &swf, &mut root);
+        player.set_play("walk", &swf, &mut root);
+        assert_eq!(
+            player.take_animation_events(),
+            [AnimationEvent::Started(Some("walk".into()))]
+        );
+
+        // 播放完毕并触发完成事件后，停在最后一帧
+        player.seek(5, &swf, &mut root);
+        player.set_completed(true);
+        player.set_play("idle", &swf, &mut root);
+        assert_eq!(
+            player.take_animation_events(),
+            [AnimationEvent::Started(Some("idle".into()))]
+        );
+    }
+
+    #[test]
+    fn looping_does_not_interrupt() {
+        let (mut player, mut root, swf) =
+            play(FlashPlayer::from_animation_name("walk").with_looping(true));
+        player.seek(5, &swf, &mut root);
+        player.loop_animation(&swf, &mut root);
+        player.loop_animation(&swf, &mut root);
+        assert_eq!(
+            player.take_animation_events(),
+            [
+                AnimationEvent::Looped(Some("walk".into()), 1),
+                AnimationEvent::Looped(Some("walk".into()), 2),
+            ]
+        );
+        assert_eq!(player.current_frame(), 1);
+    }
+
+    #[test]
+    fn timer_advances_every_elapsed_frame() {
+        let mut timer = FlashPlayerTimer::from_frame_rate(32.0);
+        assert_eq!(timer.tick_frames(Duration::from_secs(10)), 320);
+        assert_eq!(timer.tick_frames(Duration::from_millis(20)), 0);
+        assert_eq!(timer.tick_frames(Duration::from_millis(30)), 1);
     }
 }
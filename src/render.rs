@@ -88,11 +88,17 @@ use crate::{
         },
         pipeline::{
             BEVEL_FILTER_SHADER_HANDLE, BLUR_FILTER_SHADER_HANDLE,
-            COLOR_MATRIX_FILTER_SHADER_HANDLE, GLOW_FILTER_SHADER_HANDLE,
-            OFFSCREEN_COMMON_SHADER_HANDLE, OFFSCREEN_MESH2D_BITMAP_SHADER_HANDLE,
-            OFFSCREEN_MESH2D_GRADIENT_SHADER_HANDLE, OFFSCREEN_MESH2D_SHADER_HANDLE,
-            init_bevel_filter_pipeline, init_blur_filter_pipeline,
-            init_color_matrix_filter_pipeline, init_glow_filter_pipeline,
+            COLOR_MATRIX_FILTER_SHADER_HANDLE, COMPLEX_BLEND_SHADER_HANDLE,
+            CONVOLUTION_FILTER_SHADER_HANDLE, DISPLACEMENT_MAP_FILTER_SHADER_HANDLE,
+            DROP_SHADOW_FILTER_SHADER_HANDLE, GLOW_FILTER_SHADER_HANDLE,
+            GRADIENT_BEVEL_FILTER_SHADER_HANDLE, GRADIENT_GLOW_FILTER_SHADER_HANDLE,
+            MASK_SHADER_HANDLE, OFFSCREEN_COMMON_SHADER_HANDLE,
+            OFFSCREEN_MESH2D_BITMAP_SHADER_HANDLE, OFFSCREEN_MESH2D_GRADIENT_SHADER_HANDLE,
+            OFFSCREEN_MESH2D_SHADER_HANDLE, init_bevel_filter_pipeline, init_blur_filter_pipeline,
+            init_color_matrix_filter_pipeline, init_complex_blend_pipeline,
+            init_convolution_filter_pipeline, init_displacement_map_filter_pipeline,
+            init_drop_shadow_filter_pipeline, init_glow_filter_pipeline,
+            init_gradient_filter_pipeline, init_mask_pipeline,
         },
     },
 };
@@ -137,6 +143,12 @@ impl Plugin for FlashRenderPlugin {
                     init_color_matrix_filter_pipeline,
                     init_glow_filter_pipeline,
                     init_bevel_filter_pipeline,
+                    init_drop_shadow_filter_pipeline,
+                    init_gradient_filter_pipeline,
+                    init_convolution_filter_pipeline,
+                    init_displacement_map_filter_pipeline,
+                    init_mask_pipeline,
+                    init_complex_blend_pipeline,
                 ),
             )
             .add_systems(ExtractSchedule, extract_part_mesh2d_and_material);
@@ -1010,4 +1022,46 @@ fn load_shaders(app: &mut App) {
         "render/shaders/filters/bevel.wgsl",
         Shader::from_wgsl
     );
+    load_internal_asset!(
+        app,
+        DROP_SHADOW_FILTER_SHADER_HANDLE,
+        "render/shaders/filters/drop_shadow.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        GRADIENT_GLOW_FILTER_SHADER_HANDLE,
+        "render/shaders/filters/gradient_glow.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        GRADIENT_BEVEL_FILTER_SHADER_HANDLE,
+        "render/shaders/filters/gradient_bevel.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        CONVOLUTION_FILTER_SHADER_HANDLE,
+        "render/shaders/filters/convolution.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        DISPLACEMENT_MAP_FILTER_SHADER_HANDLE,
+        "render/shaders/filters/displacement_map.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        MASK_SHADER_HANDLE,
+        "render/shaders/mask.wgsl",
+        Shader::from_wgsl
+    );
+    load_internal_asset!(
+        app,
+        COMPLEX_BLEND_SHADER_HANDLE,
+        "render/shaders/complex_blend.wgsl",
+        Shader::from_wgsl
+    );
 }
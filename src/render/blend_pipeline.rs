@@ -23,13 +23,26 @@ pub enum ComplexBlend {
 }
 
 #[derive(Clone, Copy, Debug)]
-#[allow(dead_code)]
 pub enum BlendMode {
     Trivial(TrivialBlend),
-    /// TODO: 需要抓取屏幕纹理进行混合
+    /// 复杂混合模式需要在离屏图层中与背景混合，不会直接进入形状的渲染管线
     Complex(ComplexBlend),
 }
 
+impl ComplexBlend {
+    /// 混合着色器中使用的模式编号
+    pub fn shader_mode(self) -> u32 {
+        match self {
+            ComplexBlend::Difference => 1,
+            ComplexBlend::Invert => 2,
+            ComplexBlend::Alpha => 3,
+            ComplexBlend::Erase => 4,
+            ComplexBlend::Overlay => 5,
+            ComplexBlend::HardLight => 6,
+        }
+    }
+}
+
 impl From<swf::BlendMode> for BlendMode {
     fn from(mode: swf::BlendMode) -> BlendMode {
         match mode {
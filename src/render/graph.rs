@@ -1,6 +1,8 @@
+mod blend_node;
 mod filter_driven_node;
 mod filter_node;
 mod main_transparent_pass_2d_node;
+mod mask_node;
 mod upscaling;
 
 use bevy::{
@@ -28,9 +30,9 @@ use crate::{
     assets::MaterialType,
     render::{
         graph::{
-            filter_node::FilterPostProcessingNode,
+            blend_node::OffscreenComplexBlendNode, filter_node::FilterPostProcessingNode,
             main_transparent_pass_2d_node::OffscreenMainTransparentPass2dNode,
-            upscaling::OffscreenUpscalingNode,
+            mask_node::OffscreenMaskNode, upscaling::OffscreenUpscalingNode,
         },
         material::{BitmapMaterial, GradientMaterial},
     },
@@ -43,6 +45,8 @@ pub struct OffscreenCore2d;
 pub enum OffscreenNode2d {
     MainTransparentPass,
     FilterPostProcessing,
+    Mask,
+    ComplexBlend,
     Upscaling,
 }
 
@@ -69,6 +73,14 @@ impl Plugin for FlashFilterRenderPlugin {
                 OffscreenCore2d,
                 OffscreenNode2d::FilterPostProcessing,
             )
+            .add_render_graph_node::<ViewNodeRunner<OffscreenMaskNode>>(
+                OffscreenCore2d,
+                OffscreenNode2d::Mask,
+            )
+            .add_render_graph_node::<ViewNodeRunner<OffscreenComplexBlendNode>>(
+                OffscreenCore2d,
+                OffscreenNode2d::ComplexBlend,
+            )
             .add_render_graph_node::<ViewNodeRunner<OffscreenUpscalingNode>>(
                 OffscreenCore2d,
                 OffscreenNode2d::Upscaling,
@@ -78,6 +90,8 @@ impl Plugin for FlashFilterRenderPlugin {
                 (
                     OffscreenNode2d::MainTransparentPass,
                     OffscreenNode2d::FilterPostProcessing,
+                    OffscreenNode2d::Mask,
+                    OffscreenNode2d::ComplexBlend,
                     OffscreenNode2d::Upscaling,
                 ),
             );
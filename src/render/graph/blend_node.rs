@@ -0,0 +1,79 @@
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::ViewNode;
+use bevy::render::render_resource::{
+    BindGroupEntries, BufferInitDescriptor, BufferUsages, Operations, PipelineCache,
+    RenderPassColorAttachment, RenderPassDescriptor,
+};
+use bevy::render::texture::GpuImage;
+
+use crate::render::offscreen_texture::{ExtractedOffscreenTexture, ViewTarget};
+use crate::render::pipeline::{ComplexBlendPipeline, ComplexBlendUniform};
+
+/// 将图层以复杂混合模式混合到离屏纹理（背景）上
+#[derive(Default)]
+pub struct OffscreenComplexBlendNode;
+
+impl ViewNode for OffscreenComplexBlendNode {
+    type ViewQuery = (&'static ExtractedOffscreenTexture, &'static ViewTarget);
+
+    fn run<'w>(
+        &self,
+        _graph: &mut bevy::render::render_graph::RenderGraphContext,
+        render_context: &mut bevy::render::renderer::RenderContext<'w>,
+        (offscreen_texture, view_target): bevy::ecs::query::QueryItem<'w, '_, Self::ViewQuery>,
+        world: &'w bevy::ecs::world::World,
+    ) -> Result<(), bevy::render::render_graph::NodeRunError> {
+        let Some((layer, complex_blend)) = offscreen_texture.blend else {
+            return Ok(());
+        };
+        let images = world.resource::<RenderAssets<GpuImage>>();
+        let Some(layer) = images.get(layer) else {
+            return Ok(());
+        };
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let complex_blend_pipeline = world.resource::<ComplexBlendPipeline>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(complex_blend_pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+
+        let render_device = render_context.render_device();
+        let blend_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("complex_blend_uniform"),
+            contents: bytemuck::cast_slice(&[ComplexBlendUniform {
+                mode: complex_blend.shader_mode(),
+                ..Default::default()
+            }]),
+            usage: BufferUsages::UNIFORM,
+        });
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_device.create_bind_group(
+            Some("complex_blend_bind_group"),
+            &complex_blend_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &complex_blend_pipeline.sampler,
+                &layer.texture_view,
+                blend_buffer.as_entire_binding(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("complex_blend_render_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
@@ -1,23 +1,31 @@
 use crate::render::offscreen_texture::{ExtractedOffscreenTexture, ViewTarget};
 use crate::render::pipeline::{
     BevelFilterPipeline, BevelUniform, BlurFilterPipeline, BlurUniform, ColorMatrixFilterPipeline,
-    ColorMatrixUniform, GlowFilterPipeline, GlowFilterUniform, get_filter_vertex_with_double_blur,
+    ColorMatrixUniform, ConvolutionFilterPipeline, ConvolutionUniform,
+    DisplacementMapFilterPipeline, DisplacementMapUniform, DropShadowFilterPipeline,
+    FILTER_VERTICES, GlowFilterPipeline, GlowFilterUniform, GradientFilterPipeline,
+    GradientFilterUniform, get_filter_vertex_with_blur, get_filter_vertex_with_double_blur,
 };
+use crate::swf_runtime::filter::DisplacementMapFilterMode;
 use crate::swf_runtime::filter::Filter::{
-    BevelFilter, BlurFilter, ColorMatrixFilter, ConvolutionFilter, DropShadowFilter, GlowFilter,
-    GradientBevelFilter, GradientGlowFilter,
+    BevelFilter, BlurFilter, ColorMatrixFilter, ConvolutionFilter, DisplacementMapFilter,
+    DropShadowFilter, GlowFilter, GradientBevelFilter, GradientGlowFilter,
 };
-use bevy::log::info_once;
+use crate::swf_runtime::filter::gradient_filter_blur;
 use bevy::math::UVec2;
+use bevy::render::render_asset::RenderAssets;
 use bevy::render::render_graph::ViewNode;
 use bevy::render::render_phase::TrackedRenderPass;
 use bevy::render::render_resource::{
-    BindGroupEntries, BufferInitDescriptor, BufferUsages, IndexFormat, Operations, PipelineCache,
-    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, TexelCopyTextureInfo,
-    TextureAspect, TextureDescriptor, TextureDimension, TextureUsages, TextureView,
-    TextureViewDescriptor,
+    BindGroupEntries, Buffer, BufferInitDescriptor, BufferUsages, Extent3d, IndexFormat,
+    Operations, PipelineCache, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
+    TexelCopyTextureInfo, TextureAspect, TextureDataOrder, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
 };
-use bevy::render::renderer::RenderContext;
+use bevy::render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy::render::texture::GpuImage;
+
+const QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
 
 #[derive(Default)]
 pub struct FilterPostProcessingNode;
@@ -37,6 +45,12 @@ impl ViewNode for FilterPostProcessingNode {
         let color_matrix_filter_pipeline = world.resource::<ColorMatrixFilterPipeline>();
         let glow_filter_pipeline = world.resource::<GlowFilterPipeline>();
         let bevel_filter_pipeline = world.resource::<BevelFilterPipeline>();
+        let drop_shadow_filter_pipeline = world.resource::<DropShadowFilterPipeline>();
+        let gradient_filter_pipeline = world.resource::<GradientFilterPipeline>();
+        let convolution_filter_pipeline = world.resource::<ConvolutionFilterPipeline>();
+        let displacement_map_filter_pipeline = world.resource::<DisplacementMapFilterPipeline>();
+        let render_queue = world.resource::<RenderQueue>();
+        let images = world.resource::<RenderAssets<GpuImage>>();
 
         // 以下算法均来自于Ruffle
         let size = offscreen_texture.size;
@@ -270,17 +284,373 @@ impl ViewNode for FilterPostProcessingNode {
                     render_pass.set_index_buffer(indices_buffer.slice(..), 0, IndexFormat::Uint32);
                     render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
                 }
-                DropShadowFilter(..) => {
-                    info_once!("DropShadowFilter 滤镜尚未实现，我需要帮助!!!");
+                DropShadowFilter(drop_shadow_filter) => {
+                    let Some(blur_filter_render_pipeline) =
+                        pipeline_cache.get_render_pipeline(blur_filter_pipeline.pipeline_id)
+                    else {
+                        continue;
+                    };
+                    let Some(drop_shadow_filter_render_pipeline) =
+                        pipeline_cache.get_render_pipeline(drop_shadow_filter_pipeline.pipeline_id)
+                    else {
+                        continue;
+                    };
+                    let temp_texture_view = copy_source_texture(render_context, view_target);
+                    apply_blur(
+                        &drop_shadow_filter.inner_blur_filter(),
+                        render_context,
+                        blur_filter_render_pipeline,
+                        blur_filter_pipeline,
+                        view_target,
+                        size,
+                    );
+                    let post_process = view_target.post_process_write();
+
+                    let render_device = render_context.render_device();
+                    let drop_shadow_buffer =
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("drop_shadow_filter_buffer"),
+                            contents: bytemuck::cast_slice(&[GlowFilterUniform {
+                                color: [
+                                    f32::from(drop_shadow_filter.color.r) / 255.0,
+                                    f32::from(drop_shadow_filter.color.g) / 255.0,
+                                    f32::from(drop_shadow_filter.color.b) / 255.0,
+                                    f32::from(drop_shadow_filter.color.a) / 255.0,
+                                ],
+                                strength: drop_shadow_filter.strength.to_f32(),
+                                inner: if drop_shadow_filter.is_inner() { 1 } else { 0 },
+                                knockout: if drop_shadow_filter.is_knockout() {
+                                    1
+                                } else {
+                                    0
+                                },
+                                composite_source: if drop_shadow_filter.hide_object() {
+                                    0
+                                } else {
+                                    1
+                                },
+                            }]),
+                            usage: BufferUsages::UNIFORM,
+                        });
+                    let vertex_buffer =
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("drop_shadow_filter_with_uv"),
+                            contents: bytemuck::cast_slice(&get_filter_vertex_with_blur(
+                                drop_shadow_filter.distance.to_f32(),
+                                drop_shadow_filter.angle.to_f32(),
+                                size.as_vec2(),
+                            )),
+                            usage: BufferUsages::VERTEX,
+                        });
+                    let indices_buffer = create_quad_indices_buffer(render_device);
+                    let bind_group = render_device.create_bind_group(
+                        "drop_shadow_filter_bind_group",
+                        &drop_shadow_filter_pipeline.layout,
+                        &BindGroupEntries::sequential((
+                            &temp_texture_view,
+                            &drop_shadow_filter_pipeline.sampler,
+                            drop_shadow_buffer.as_entire_binding(),
+                            post_process.source,
+                        )),
+                    );
+
+                    let mut render_pass = get_render_pass(
+                        render_context,
+                        post_process.destination,
+                        "drop_shadow_filter_render_pass",
+                    );
+                    render_pass.set_render_pipeline(drop_shadow_filter_render_pipeline);
+                    render_pass.set_bind_group(0, &bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(indices_buffer.slice(..), 0, IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, 0..1);
                 }
-                ConvolutionFilter(..) => {
-                    info_once!("ConvolutionFilter 滤镜尚未实现，我需要帮助!!!");
+                GradientGlowFilter(gradient_filter) | GradientBevelFilter(gradient_filter) => {
+                    let bevel = matches!(filter, GradientBevelFilter(_));
+                    let Some(blur_filter_render_pipeline) =
+                        pipeline_cache.get_render_pipeline(blur_filter_pipeline.pipeline_id)
+                    else {
+                        continue;
+                    };
+                    let Some(gradient_filter_render_pipeline) =
+                        pipeline_cache.get_render_pipeline(if bevel {
+                            gradient_filter_pipeline.bevel_pipeline_id
+                        } else {
+                            gradient_filter_pipeline.glow_pipeline_id
+                        })
+                    else {
+                        continue;
+                    };
+
+                    let temp_texture_view = copy_source_texture(render_context, view_target);
+                    apply_blur(
+                        &gradient_filter_blur(gradient_filter),
+                        render_context,
+                        blur_filter_render_pipeline,
+                        blur_filter_pipeline,
+                        view_target,
+                        size,
+                    );
+                    let post_process = view_target.post_process_write();
+
+                    let render_device = render_context.render_device();
+                    let gradient_texture = render_device.create_texture_with_data(
+                        render_queue,
+                        &TextureDescriptor {
+                            label: Some("gradient_filter_ramp"),
+                            size: Extent3d {
+                                width: 256,
+                                height: 1,
+                                depth_or_array_layers: 1,
+                            },
+                            mip_level_count: 1,
+                            sample_count: 1,
+                            dimension: TextureDimension::D2,
+                            format: TextureFormat::Rgba8Unorm,
+                            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                            view_formats: &[],
+                        },
+                        TextureDataOrder::LayerMajor,
+                        &gradient_filter_ramp(&gradient_filter.colors),
+                    );
+                    let gradient_texture_view =
+                        gradient_texture.create_view(&TextureViewDescriptor::default());
+                    let gradient_buffer =
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("gradient_filter_buffer"),
+                            contents: bytemuck::cast_slice(&[GradientFilterUniform {
+                                strength: gradient_filter.strength.to_f32(),
+                                filter_type: if gradient_filter.is_on_top() {
+                                    2
+                                } else if gradient_filter.is_inner() {
+                                    1
+                                } else {
+                                    0
+                                },
+                                knockout: if gradient_filter.is_knockout() { 1 } else { 0 },
+                                composite_source: 1,
+                            }]),
+                            usage: BufferUsages::UNIFORM,
+                        });
+                    let distance = gradient_filter.distance.to_f32();
+                    let angle = gradient_filter.angle.to_f32();
+                    let vertex_buffer = if bevel {
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("gradient_bevel_filter_with_double_uv"),
+                            contents: bytemuck::cast_slice(&get_filter_vertex_with_double_blur(
+                                distance,
+                                angle,
+                                size.as_vec2(),
+                            )),
+                            usage: BufferUsages::VERTEX,
+                        })
+                    } else {
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("gradient_glow_filter_with_uv"),
+                            contents: bytemuck::cast_slice(&get_filter_vertex_with_blur(
+                                distance,
+                                angle,
+                                size.as_vec2(),
+                            )),
+                            usage: BufferUsages::VERTEX,
+                        })
+                    };
+                    let indices_buffer = create_quad_indices_buffer(render_device);
+                    let bind_group = render_device.create_bind_group(
+                        "gradient_filter_bind_group",
+                        &gradient_filter_pipeline.layout,
+                        &BindGroupEntries::sequential((
+                            &temp_texture_view,
+                            &gradient_filter_pipeline.sampler,
+                            gradient_buffer.as_entire_binding(),
+                            post_process.source,
+                            &gradient_texture_view,
+                        )),
+                    );
+
+                    let mut render_pass = get_render_pass(
+                        render_context,
+                        post_process.destination,
+                        "gradient_filter_render_pass",
+                    );
+                    render_pass.set_render_pipeline(gradient_filter_render_pipeline);
+                    render_pass.set_bind_group(0, &bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(indices_buffer.slice(..), 0, IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, 0..1);
                 }
-                GradientBevelFilter(..) => {
-                    info_once!("GradientBevelFilter 滤镜尚未实现，我需要帮助!!!");
+                ConvolutionFilter(convolution_filter) => {
+                    let Some(pipeline) =
+                        pipeline_cache.get_render_pipeline(convolution_filter_pipeline.pipeline_id)
+                    else {
+                        continue;
+                    };
+                    let rows = u32::from(convolution_filter.num_matrix_rows);
+                    let cols = u32::from(convolution_filter.num_matrix_cols);
+                    let matrix_len = (rows * cols) as usize;
+                    if matrix_len == 0 || convolution_filter.matrix.len() < matrix_len {
+                        continue;
+                    }
+
+                    let post_process = view_target.post_process_write();
+                    let render_device = render_context.render_device();
+                    let weights_texture = render_device.create_texture_with_data(
+                        render_queue,
+                        &TextureDescriptor {
+                            label: Some("convolution_filter_matrix"),
+                            size: Extent3d {
+                                width: cols,
+                                height: rows,
+                                depth_or_array_layers: 1,
+                            },
+                            mip_level_count: 1,
+                            sample_count: 1,
+                            dimension: TextureDimension::D2,
+                            format: TextureFormat::R32Float,
+                            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                            view_formats: &[],
+                        },
+                        TextureDataOrder::LayerMajor,
+                        bytemuck::cast_slice(&convolution_filter.matrix[..matrix_len]),
+                    );
+                    let weights_texture_view =
+                        weights_texture.create_view(&TextureViewDescriptor::default());
+                    let default_color = convolution_filter.default_color;
+                    let convolution_buffer =
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("convolution_filter_buffer"),
+                            contents: bytemuck::cast_slice(&[ConvolutionUniform {
+                                default_color: [
+                                    f32::from(default_color.r) / 255.0,
+                                    f32::from(default_color.g) / 255.0,
+                                    f32::from(default_color.b) / 255.0,
+                                    f32::from(default_color.a) / 255.0,
+                                ],
+                                // Flash 中除数为 0 时按 1 处理
+                                divisor: if convolution_filter.divisor == 0.0 {
+                                    1.0
+                                } else {
+                                    convolution_filter.divisor
+                                },
+                                bias: convolution_filter.bias,
+                                rows,
+                                cols,
+                                clamp: if convolution_filter.is_clamped() {
+                                    1
+                                } else {
+                                    0
+                                },
+                                preserve_alpha: if convolution_filter.is_preserve_alpha() {
+                                    1
+                                } else {
+                                    0
+                                },
+                                _padding_0: 0,
+                                _padding_1: 0,
+                            }]),
+                            usage: BufferUsages::UNIFORM,
+                        });
+                    let bind_group = render_device.create_bind_group(
+                        "convolution_filter_bind_group",
+                        &convolution_filter_pipeline.layout,
+                        &BindGroupEntries::sequential((
+                            post_process.source,
+                            convolution_buffer.as_entire_binding(),
+                            &weights_texture_view,
+                        )),
+                    );
+
+                    let mut render_pass = get_render_pass(
+                        render_context,
+                        post_process.destination,
+                        "convolution_filter_render_pass",
+                    );
+                    render_pass.set_render_pipeline(pipeline);
+                    render_pass.set_bind_group(0, &bind_group, &[]);
+                    render_pass.draw(0..3, 0..1);
                 }
-                GradientGlowFilter(..) => {
-                    info_once!("GradientGlowFilter 滤镜尚未实现，我需要帮助!!!");
+                DisplacementMapFilter(displacement_map_filter) => {
+                    let Some(pipeline) = pipeline_cache
+                        .get_render_pipeline(displacement_map_filter_pipeline.pipeline_id)
+                    else {
+                        continue;
+                    };
+                    let Some(map) = images.get(&displacement_map_filter.map_bitmap) else {
+                        continue;
+                    };
+
+                    let post_process = view_target.post_process_write();
+                    let render_device = render_context.render_device();
+                    let color = displacement_map_filter.color;
+                    let displacement_map_buffer =
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("displacement_map_filter_buffer"),
+                            contents: bytemuck::cast_slice(&[DisplacementMapUniform {
+                                color: [
+                                    f32::from(color.r) / 255.0,
+                                    f32::from(color.g) / 255.0,
+                                    f32::from(color.b) / 255.0,
+                                    f32::from(color.a) / 255.0,
+                                ],
+                                components: (u32::from(displacement_map_filter.component_x) << 8)
+                                    | u32::from(displacement_map_filter.component_y),
+                                mode: match displacement_map_filter.mode {
+                                    DisplacementMapFilterMode::Wrap => 0,
+                                    DisplacementMapFilterMode::Clamp => 1,
+                                    DisplacementMapFilterMode::Ignore => 2,
+                                    DisplacementMapFilterMode::Color => 3,
+                                },
+                                scale_x: displacement_map_filter.scale_x,
+                                scale_y: displacement_map_filter.scale_y,
+                                source_width: size.x as f32,
+                                source_height: size.y as f32,
+                                map_width: map.size.width as f32,
+                                map_height: map.size.height as f32,
+                                offset_x: displacement_map_filter.map_point.0 as f32
+                                    * displacement_map_filter.viewscale_x,
+                                offset_y: displacement_map_filter.map_point.1 as f32
+                                    * displacement_map_filter.viewscale_y,
+                                viewscale_x: displacement_map_filter.viewscale_x,
+                                viewscale_y: displacement_map_filter.viewscale_y,
+                            }]),
+                            usage: BufferUsages::UNIFORM,
+                        });
+                    let vertex_buffer =
+                        render_device.create_buffer_with_data(&BufferInitDescriptor {
+                            label: Some("displacement_map_filter_vertices"),
+                            contents: bytemuck::cast_slice(&FILTER_VERTICES),
+                            usage: BufferUsages::VERTEX,
+                        });
+                    let indices_buffer = create_quad_indices_buffer(render_device);
+                    // 环绕模式依靠采样器实现，其余模式在着色器中处理
+                    let source_sampler =
+                        if displacement_map_filter.mode == DisplacementMapFilterMode::Wrap {
+                            &displacement_map_filter_pipeline.repeat_sampler
+                        } else {
+                            &displacement_map_filter_pipeline.clamp_sampler
+                        };
+                    let bind_group = render_device.create_bind_group(
+                        "displacement_map_filter_bind_group",
+                        &displacement_map_filter_pipeline.layout,
+                        &BindGroupEntries::sequential((
+                            post_process.source,
+                            &map.texture_view,
+                            source_sampler,
+                            &displacement_map_filter_pipeline.clamp_sampler,
+                            displacement_map_buffer.as_entire_binding(),
+                        )),
+                    );
+
+                    let mut render_pass = get_render_pass(
+                        render_context,
+                        post_process.destination,
+                        "displacement_map_filter_render_pass",
+                    );
+                    render_pass.set_render_pipeline(pipeline);
+                    render_pass.set_bind_group(0, &bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(indices_buffer.slice(..), 0, IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, 0..1);
                 }
             }
         }
@@ -358,6 +728,48 @@ fn apply_blur<'w>(
     }
 }
 
+fn create_quad_indices_buffer(render_device: &RenderDevice) -> Buffer {
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("filter_quad_indices"),
+        contents: bytemuck::cast_slice(&QUAD_INDICES),
+        usage: BufferUsages::INDEX,
+    })
+}
+
+/// 将渐变滤镜的颜色记录插值为 256x1 的色带（非预乘 RGBA）
+fn gradient_filter_ramp(records: &[swf::GradientRecord]) -> Vec<u8> {
+    let mut ramp = Vec::with_capacity(256 * 4);
+    for i in 0..=u8::MAX {
+        let color = match records.iter().position(|record| record.ratio >= i) {
+            Some(0) => records[0].color,
+            Some(index) => {
+                let start = &records[index - 1];
+                let end = &records[index];
+                let t = f32::from(i - start.ratio) / f32::from(end.ratio - start.ratio);
+                let lerp =
+                    |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+                swf::Color {
+                    r: lerp(start.color.r, end.color.r),
+                    g: lerp(start.color.g, end.color.g),
+                    b: lerp(start.color.b, end.color.b),
+                    a: lerp(start.color.a, end.color.a),
+                }
+            }
+            None => records.last().map_or(
+                swf::Color {
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 0,
+                },
+                |record| record.color,
+            ),
+        };
+        ramp.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+    ramp
+}
+
 fn copy_source_texture<'a, 'w>(
     render_context: &'a mut RenderContext<'w>,
     view_target: &ViewTarget,
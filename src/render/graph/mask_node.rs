@@ -0,0 +1,66 @@
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::ViewNode;
+use bevy::render::render_resource::{
+    BindGroupEntries, Operations, PipelineCache, RenderPassColorAttachment, RenderPassDescriptor,
+};
+use bevy::render::texture::GpuImage;
+
+use crate::render::offscreen_texture::{ExtractedOffscreenTexture, ViewTarget};
+use crate::render::pipeline::MaskPipeline;
+
+/// 将离屏图层乘以遮罩纹理的 alpha
+#[derive(Default)]
+pub struct OffscreenMaskNode;
+
+impl ViewNode for OffscreenMaskNode {
+    type ViewQuery = (&'static ExtractedOffscreenTexture, &'static ViewTarget);
+
+    fn run<'w>(
+        &self,
+        _graph: &mut bevy::render::render_graph::RenderGraphContext,
+        render_context: &mut bevy::render::renderer::RenderContext<'w>,
+        (offscreen_texture, view_target): bevy::ecs::query::QueryItem<'w, '_, Self::ViewQuery>,
+        world: &'w bevy::ecs::world::World,
+    ) -> Result<(), bevy::render::render_graph::NodeRunError> {
+        let Some(mask) = offscreen_texture.mask else {
+            return Ok(());
+        };
+        let images = world.resource::<RenderAssets<GpuImage>>();
+        let Some(mask) = images.get(mask) else {
+            return Ok(());
+        };
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let mask_pipeline = world.resource::<MaskPipeline>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(mask_pipeline.pipeline_id) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            Some("mask_bind_group"),
+            &mask_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &mask_pipeline.sampler,
+                &mask.texture_view,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("mask_render_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use bevy::{
     app::Plugin,
+    asset::{AssetId, Handle},
     camera::{NormalizedRenderTarget, RenderTarget},
     color::{Color, LinearRgba},
     ecs::{
@@ -13,6 +14,7 @@ use bevy::{
         schedule::IntoScheduleConfigs,
         system::{Commands, Query, Res, ResMut},
     },
+    image::Image,
     log::error,
     math::{Mat4, UVec2, Vec3},
     mesh::MeshVertexBufferLayoutRef,
@@ -39,6 +41,7 @@ use bevy::{
 
 use crate::assets::MaterialType;
 use crate::commands::ShapeCommand;
+use crate::render::blend_pipeline::ComplexBlend;
 use crate::render::material::{BlendModelKey, TransformUniform};
 use crate::{
     commands::OffscreenDrawShapes,
@@ -63,6 +66,10 @@ pub struct OffscreenTexture {
     pub clear_color: Color,
     pub filters: Vec<Filter>,
     pub scale: Vec3,
+    /// 遮罩纹理，渲染完成后会乘以遮罩的 alpha
+    pub mask: Option<Handle<Image>>,
+    /// 以此纹理为背景，按混合模式混合的图层
+    pub blend: Option<(Handle<Image>, ComplexBlend)>,
 }
 
 #[derive(Component, Debug, Deref, DerefMut, Reflect, Clone)]
@@ -87,6 +94,8 @@ pub struct ExtractedOffscreenTexture {
     pub render_graph: InternedRenderSubGraph,
     pub filters: Vec<Filter>,
     pub scale: Vec3,
+    pub mask: Option<AssetId<Image>>,
+    pub blend: Option<(AssetId<Image>, ComplexBlend)>,
 }
 
 pub fn extract_offscreen_textures(
@@ -117,6 +126,11 @@ pub fn extract_offscreen_textures(
             render_graph: render_graph.0,
             filters: offscreen_texture.filters.clone(),
             scale: offscreen_texture.scale,
+            mask: offscreen_texture.mask.as_ref().map(Handle::id),
+            blend: offscreen_texture
+                .blend
+                .as_ref()
+                .map(|(layer, blend)| (layer.id(), *blend)),
         });
         render_phases.insert_or_clear(render_entity.into());
         live_entities.insert(render_entity.into());
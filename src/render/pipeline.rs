@@ -11,13 +11,13 @@ use bevy::{
     mesh::{Mesh, PrimitiveTopology, VertexBufferLayout},
     render::{
         render_resource::{
-            AsBindGroup, BindGroupLayout, BindGroupLayoutEntries, BlendComponent, BlendFactor,
-            BlendOperation, BlendState, BufferUsages, BufferVec, CachedRenderPipelineId,
-            ColorTargetState, ColorWrites, DynamicUniformBuffer, FragmentState, FrontFace,
-            MultisampleState, PipelineCache, PolygonMode, PrimitiveState, RenderPipelineDescriptor,
-            Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType,
-            SpecializedMeshPipeline, TextureFormat, TextureSampleType, VertexFormat, VertexState,
-            VertexStepMode,
+            AddressMode, AsBindGroup, BindGroupLayout, BindGroupLayoutEntries, BlendComponent,
+            BlendFactor, BlendOperation, BlendState, BufferUsages, BufferVec,
+            CachedRenderPipelineId, ColorTargetState, ColorWrites, DynamicUniformBuffer,
+            FragmentState, FrontFace, MultisampleState, PipelineCache, PolygonMode, PrimitiveState,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
+            ShaderType, SpecializedMeshPipeline, TextureFormat, TextureSampleType, VertexFormat,
+            VertexState, VertexStepMode,
             binding_types::{sampler, texture_2d, uniform_buffer},
         },
         renderer::{RenderDevice, RenderQueue},
@@ -53,6 +53,26 @@ pub const GLOW_FILTER_SHADER_HANDLE: Handle<Shader> =
 pub const BEVEL_FILTER_SHADER_HANDLE: Handle<Shader> =
     uuid_handle!("e2f8a9d6-3c7b-42f1-8e9d-5a6b4c3d2e1f");
 
+pub const DROP_SHADOW_FILTER_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("5b7e2c91-4f3a-4d68-9e15-8a0c6b3d2f47");
+
+pub const GRADIENT_GLOW_FILTER_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("9d1f4a63-2e8b-4c05-b7a9-6e3c5d8f1a20");
+
+pub const GRADIENT_BEVEL_FILTER_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("4a6c8e20-b1d3-4f57-9a8c-2e4f6b8d0c13");
+
+pub const CONVOLUTION_FILTER_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("c8e0a2b4-6d1f-4e39-8b57-1a3c5e7f9b02");
+
+pub const DISPLACEMENT_MAP_FILTER_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("2f4b6d8a-0c3e-4a51-b7d9-e1f3a5c7b9d4");
+
+pub const MASK_SHADER_HANDLE: Handle<Shader> = uuid_handle!("7c4e9b2a-5d13-4f86-a0e7-3b9d1c6f8e24");
+
+pub const COMPLEX_BLEND_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("3f8a1d6c-92b4-4e57-b0c3-6d2e9a4f1b78");
+
 bitflags::bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
     #[repr(transparent)]
@@ -313,6 +333,65 @@ pub struct BevelUniform {
     pub composite_source: u32, // undocumented flash feature, another bool
 }
 
+/// 渐变发光和渐变斜角滤镜
+#[repr(C)]
+#[derive(Copy, Clone, Debug, ShaderType, Pod, Zeroable, PartialEq)]
+pub struct GradientFilterUniform {
+    pub strength: f32,
+    pub filter_type: u32,      // 0 outer, 1 inner, 2 full
+    pub knockout: u32,         // a wasteful bool, but we need to be aligned anyway
+    pub composite_source: u32, // undocumented flash feature, another bool
+}
+
+/// 卷积滤镜，矩阵本身通过纹理传入
+#[repr(C)]
+#[derive(Copy, Clone, Debug, ShaderType, Pod, Zeroable, PartialEq)]
+pub struct ConvolutionUniform {
+    pub default_color: [f32; 4],
+    pub divisor: f32,
+    pub bias: f32,
+    pub rows: u32,
+    pub cols: u32,
+    pub clamp: u32,
+    pub preserve_alpha: u32,
+    pub _padding_0: u32,
+    pub _padding_1: u32,
+}
+
+/// 置换图滤镜
+#[repr(C)]
+#[derive(Copy, Clone, Debug, ShaderType, Pod, Zeroable, PartialEq)]
+pub struct DisplacementMapUniform {
+    pub color: [f32; 4],
+    pub components: u32, // 00000000 00000000 XXXXXXXX YYYYYYYY
+    pub mode: u32,       // 0 wrap, 1 clamp, 2 ignore, 3 color
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub source_width: f32,
+    pub source_height: f32,
+    pub map_width: f32,
+    pub map_height: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub viewscale_x: f32,
+    pub viewscale_y: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, ShaderType, Pod, Zeroable, PartialEq)]
+pub struct FilterVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, ShaderType, Pod, Zeroable, PartialEq)]
+pub struct FilterVertexWithBlur {
+    pub position: [f32; 2],
+    pub source_uv: [f32; 2],
+    pub blur_uv: [f32; 2],
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, ShaderType, Pod, Zeroable, PartialEq)]
 pub struct FilterVertexWithDoubleBlur {
@@ -561,6 +640,405 @@ pub(crate) fn init_bevel_filter_pipeline(
     });
 }
 
+#[derive(Resource)]
+pub struct DropShadowFilterPipeline {
+    pub layout: BindGroupLayout,
+    pub sampler: Sampler,
+    pub pipeline_id: CachedRenderPipelineId,
+}
+
+pub(crate) fn init_drop_shadow_filter_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        "drop_shadow_filter_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                uniform_buffer::<GlowFilterUniform>(false),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+            ),
+        ),
+    );
+    let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+    let descriptor = RenderPipelineDescriptor {
+        label: Some(Cow::from("drop_shadow_filter_render_pipeline")),
+        layout: vec![layout.clone()],
+        push_constant_ranges: vec![],
+        vertex: VertexState {
+            shader: DROP_SHADOW_FILTER_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("vertex".into()),
+            buffers: vec![VertexBufferLayout::from_vertex_formats(
+                VertexStepMode::Vertex,
+                vec![
+                    VertexFormat::Float32x2,
+                    VertexFormat::Float32x2,
+                    VertexFormat::Float32x2,
+                ],
+            )],
+        },
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        fragment: Some(FragmentState {
+            shader: DROP_SHADOW_FILTER_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("fragment".into()),
+            targets: vec![Some(ColorTargetState {
+                format: TextureFormat::Rgba8Unorm,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        zero_initialize_workgroup_memory: false,
+    };
+
+    let pipeline_id = pipeline_cache.queue_render_pipeline(descriptor);
+
+    commands.insert_resource(DropShadowFilterPipeline {
+        layout,
+        sampler,
+        pipeline_id,
+    });
+}
+
+/// 渐变发光与渐变斜角滤镜共用的绑定布局，最后一个纹理为 256x1 的渐变色带
+#[derive(Resource)]
+pub struct GradientFilterPipeline {
+    pub layout: BindGroupLayout,
+    pub sampler: Sampler,
+    pub glow_pipeline_id: CachedRenderPipelineId,
+    pub bevel_pipeline_id: CachedRenderPipelineId,
+}
+
+pub(crate) fn init_gradient_filter_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        "gradient_filter_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                uniform_buffer::<GradientFilterUniform>(false),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+            ),
+        ),
+    );
+    let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+    let descriptor = |label: &'static str, shader: Handle<Shader>, blur_uv_count: usize| {
+        RenderPipelineDescriptor {
+            label: Some(Cow::from(label)),
+            layout: vec![layout.clone()],
+            push_constant_ranges: vec![],
+            vertex: VertexState {
+                shader: shader.clone(),
+                shader_defs: vec![],
+                entry_point: Some("vertex".into()),
+                buffers: vec![VertexBufferLayout::from_vertex_formats(
+                    VertexStepMode::Vertex,
+                    vec![VertexFormat::Float32x2; 2 + blur_uv_count],
+                )],
+            },
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            fragment: Some(FragmentState {
+                shader,
+                shader_defs: vec![],
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: TextureFormat::Rgba8Unorm,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            zero_initialize_workgroup_memory: false,
+        }
+    };
+
+    let glow_pipeline_id = pipeline_cache.queue_render_pipeline(descriptor(
+        "gradient_glow_filter_render_pipeline",
+        GRADIENT_GLOW_FILTER_SHADER_HANDLE,
+        1,
+    ));
+    let bevel_pipeline_id = pipeline_cache.queue_render_pipeline(descriptor(
+        "gradient_bevel_filter_render_pipeline",
+        GRADIENT_BEVEL_FILTER_SHADER_HANDLE,
+        2,
+    ));
+
+    commands.insert_resource(GradientFilterPipeline {
+        layout,
+        sampler,
+        glow_pipeline_id,
+        bevel_pipeline_id,
+    });
+}
+
+#[derive(Resource)]
+pub struct ConvolutionFilterPipeline {
+    pub layout: BindGroupLayout,
+    pub pipeline_id: CachedRenderPipelineId,
+}
+
+pub(crate) fn init_convolution_filter_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    fullscreen_shader: Res<FullscreenShader>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        "convolution_filter_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                uniform_buffer::<ConvolutionUniform>(false),
+                // 卷积矩阵，R32Float 不可过滤，只通过 textureLoad 读取
+                texture_2d(TextureSampleType::Float { filterable: false }),
+            ),
+        ),
+    );
+
+    let descriptor = RenderPipelineDescriptor {
+        label: Some(Cow::from("convolution_filter_render_pipeline")),
+        layout: vec![layout.clone()],
+        push_constant_ranges: vec![],
+        vertex: fullscreen_shader.to_vertex_state(),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        fragment: Some(FragmentState {
+            shader: CONVOLUTION_FILTER_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("fragment".into()),
+            targets: vec![Some(ColorTargetState {
+                format: TextureFormat::Rgba8Unorm,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        zero_initialize_workgroup_memory: false,
+    };
+
+    let pipeline_id = pipeline_cache.queue_render_pipeline(descriptor);
+
+    commands.insert_resource(ConvolutionFilterPipeline {
+        layout,
+        pipeline_id,
+    });
+}
+
+#[derive(Resource)]
+pub struct DisplacementMapFilterPipeline {
+    pub layout: BindGroupLayout,
+    /// 环绕模式使用的采样器
+    pub repeat_sampler: Sampler,
+    pub clamp_sampler: Sampler,
+    pub pipeline_id: CachedRenderPipelineId,
+}
+
+pub(crate) fn init_displacement_map_filter_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        "displacement_map_filter_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                sampler(SamplerBindingType::Filtering),
+                uniform_buffer::<DisplacementMapUniform>(false),
+            ),
+        ),
+    );
+    let repeat_sampler = render_device.create_sampler(&SamplerDescriptor {
+        address_mode_u: AddressMode::Repeat,
+        address_mode_v: AddressMode::Repeat,
+        ..Default::default()
+    });
+    let clamp_sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+    let descriptor = RenderPipelineDescriptor {
+        label: Some(Cow::from("displacement_map_filter_render_pipeline")),
+        layout: vec![layout.clone()],
+        push_constant_ranges: vec![],
+        vertex: VertexState {
+            shader: DISPLACEMENT_MAP_FILTER_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("main_vertex".into()),
+            buffers: vec![VertexBufferLayout::from_vertex_formats(
+                VertexStepMode::Vertex,
+                vec![VertexFormat::Float32x2, VertexFormat::Float32x2],
+            )],
+        },
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        fragment: Some(FragmentState {
+            shader: DISPLACEMENT_MAP_FILTER_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("main_fragment".into()),
+            targets: vec![Some(ColorTargetState {
+                format: TextureFormat::Rgba8Unorm,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        zero_initialize_workgroup_memory: false,
+    };
+
+    let pipeline_id = pipeline_cache.queue_render_pipeline(descriptor);
+
+    commands.insert_resource(DisplacementMapFilterPipeline {
+        layout,
+        repeat_sampler,
+        clamp_sampler,
+        pipeline_id,
+    });
+}
+
+/// 遮罩合成，将图层乘以遮罩纹理的 alpha
+#[derive(Resource)]
+pub struct MaskPipeline {
+    pub layout: BindGroupLayout,
+    pub sampler: Sampler,
+    pub pipeline_id: CachedRenderPipelineId,
+}
+
+pub(crate) fn init_mask_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    fullscreen_shader: Res<FullscreenShader>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        "mask_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+            ),
+        ),
+    );
+    let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+    let descriptor = RenderPipelineDescriptor {
+        label: Some(Cow::from("mask_render_pipeline")),
+        layout: vec![layout.clone()],
+        push_constant_ranges: vec![],
+        vertex: fullscreen_shader.to_vertex_state(),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        fragment: Some(FragmentState {
+            shader: MASK_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("fragment".into()),
+            targets: vec![Some(ColorTargetState {
+                format: TextureFormat::Rgba8Unorm,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        zero_initialize_workgroup_memory: false,
+    };
+
+    let pipeline_id = pipeline_cache.queue_render_pipeline(descriptor);
+
+    commands.insert_resource(MaskPipeline {
+        layout,
+        sampler,
+        pipeline_id,
+    });
+}
+
+/// 复杂混合
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, ShaderType, Pod, Zeroable, PartialEq)]
+pub struct ComplexBlendUniform {
+    pub mode: u32,
+    pub _padding_0: u32,
+    pub _padding_1: u32,
+    pub _padding_2: u32,
+}
+
+/// 复杂混合，将图层以给定的混合模式混合到背景上
+#[derive(Resource)]
+pub struct ComplexBlendPipeline {
+    pub layout: BindGroupLayout,
+    pub sampler: Sampler,
+    pub pipeline_id: CachedRenderPipelineId,
+}
+
+pub(crate) fn init_complex_blend_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    fullscreen_shader: Res<FullscreenShader>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        "complex_blend_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                uniform_buffer::<ComplexBlendUniform>(false),
+            ),
+        ),
+    );
+    let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+    let descriptor = RenderPipelineDescriptor {
+        label: Some(Cow::from("complex_blend_render_pipeline")),
+        layout: vec![layout.clone()],
+        push_constant_ranges: vec![],
+        vertex: fullscreen_shader.to_vertex_state(),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        fragment: Some(FragmentState {
+            shader: COMPLEX_BLEND_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: Some("fragment".into()),
+            targets: vec![Some(ColorTargetState {
+                format: TextureFormat::Rgba8Unorm,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        zero_initialize_workgroup_memory: false,
+    };
+
+    let pipeline_id = pipeline_cache.queue_render_pipeline(descriptor);
+
+    commands.insert_resource(ComplexBlendPipeline {
+        layout,
+        sampler,
+        pipeline_id,
+    });
+}
+
 #[derive(Resource)]
 pub struct FilterUniformBuffers {
     pub view_uniform_buffer: DynamicUniformBuffer<Mat4>,
@@ -603,6 +1081,44 @@ impl FilterUniformBuffers {
     }
 }
 
+/// 铺满整个纹理的矩形，纹理坐标与位置一致
+pub const FILTER_VERTICES: [FilterVertex; 4] = [
+    FilterVertex {
+        position: [0.0, 0.0],
+        uv: [0.0, 0.0],
+    },
+    FilterVertex {
+        position: [1.0, 0.0],
+        uv: [1.0, 0.0],
+    },
+    FilterVertex {
+        position: [1.0, 1.0],
+        uv: [1.0, 1.0],
+    },
+    FilterVertex {
+        position: [0.0, 1.0],
+        uv: [0.0, 1.0],
+    },
+];
+
+/// 模糊纹理整体偏移 `distance`，用于投影和渐变发光
+pub fn get_filter_vertex_with_blur(
+    distance: f32,
+    angle: f32,
+    size: Vec2,
+) -> Vec<FilterVertexWithBlur> {
+    let blur_offset_x = angle.cos() * distance / size.x;
+    let blur_offset_y = angle.sin() * distance / size.y;
+    FILTER_VERTICES
+        .iter()
+        .map(|vertex| FilterVertexWithBlur {
+            position: vertex.position,
+            source_uv: vertex.uv,
+            blur_uv: [vertex.uv[0] - blur_offset_x, vertex.uv[1] - blur_offset_y],
+        })
+        .collect()
+}
+
 pub fn get_filter_vertex_with_double_blur(
     distance: f32,
     angle: f32,
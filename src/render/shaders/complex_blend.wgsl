@@ -0,0 +1,80 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct Blend {
+    mode: u32,
+    _padding_0: u32,
+    _padding_1: u32,
+    _padding_2: u32,
+}
+
+// 背景（已经绘制的内容）
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+// 需要混合的图层
+@group(0) @binding(2) var layer: texture_2d<f32>;
+@group(0) @binding(3) var<uniform> blend: Blend;
+
+// 以下混合公式来自于 Ruffle，颜色均为非预乘的颜色
+fn blend_func(src: vec3<f32>, dst: vec3<f32>) -> vec3<f32> {
+    switch blend.mode {
+        // Difference
+        case 1u: {
+            return abs(dst - src);
+        }
+        // Invert
+        case 2u: {
+            return vec3<f32>(1.0) - dst;
+        }
+        // Overlay
+        case 5u: {
+            return select(
+                vec3<f32>(1.0) - 2.0 * (vec3<f32>(1.0) - dst) * (vec3<f32>(1.0) - src),
+                2.0 * src * dst,
+                dst <= vec3<f32>(0.5),
+            );
+        }
+        // HardLight
+        case 6u: {
+            return select(
+                vec3<f32>(1.0) - 2.0 * (vec3<f32>(1.0) - dst) * (vec3<f32>(1.0) - src),
+                2.0 * src * dst,
+                src <= vec3<f32>(0.5),
+            );
+        }
+        default: {
+            return src;
+        }
+    }
+}
+
+// 输出混合结果相对背景的差值 d，背景保持原样，d 以普通混合模式叠加到背景上之后即为混合结果：
+// d.rgb + dst.rgb * (1 - d.a)
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // 纹理中的颜色均为预乘 alpha 的颜色
+    let dst = textureSample(texture, texture_sampler, in.uv);
+    let src = textureSample(layer, texture_sampler, in.uv);
+
+    // Alpha，结果为 dst * src.a
+    if blend.mode == 3u {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0 - src.a);
+    }
+    // Erase，结果为 dst * (1 - src.a)
+    if blend.mode == 4u {
+        return vec4<f32>(0.0, 0.0, 0.0, src.a);
+    }
+
+    if src.a <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+    if dst.a <= 0.0 {
+        return src;
+    }
+
+    let src_color = src.rgb / src.a;
+    let dst_color = dst.rgb / dst.a;
+    let blended = blend_func(src_color, dst_color);
+    // 重叠部分使用混合结果，其余部分保留各自的颜色，背景中未被覆盖的部分 dst * (1 - src.a) 由叠加补上
+    let color = src.rgb * (1.0 - dst.a) + blended * src.a * dst.a;
+    return vec4<f32>(color, src.a);
+}
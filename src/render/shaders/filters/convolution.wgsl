@@ -0,0 +1,58 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct Filter {
+    default_color: vec4<f32>,
+    divisor: f32,
+    bias: f32,
+    rows: u32,
+    cols: u32,
+    clamp: u32,
+    preserve_alpha: u32,
+    _padding_0: u32,
+    _padding_1: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> filter_args: Filter;
+/// cols x rows 的卷积矩阵
+@group(0) @binding(2) var weights: texture_2d<f32>;
+
+fn unmultiply(color: vec4<f32>) -> vec4<f32> {
+    if color.a <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+    return vec4<f32>(color.rgb / color.a, color.a);
+}
+
+/// 读取源像素（非预乘），超出边缘时按 clamp 设置取边缘像素或默认颜色
+fn load_source(position: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    if any(position < vec2<i32>(0)) || any(position >= size) {
+        if filter_args.clamp == 0u {
+            return filter_args.default_color;
+        }
+        return unmultiply(textureLoad(texture, clamp(position, vec2<i32>(0), size - 1), 0));
+    }
+    return unmultiply(textureLoad(texture, position, 0));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(texture));
+    let center = vec2<i32>(floor(in.position.xy));
+    let half_size = vec2<i32>(i32(filter_args.cols / 2u), i32(filter_args.rows / 2u));
+
+    var sum = vec4<f32>(0.0);
+    for (var y = 0u; y < filter_args.rows; y++) {
+        for (var x = 0u; x < filter_args.cols; x++) {
+            let offset = vec2<i32>(i32(x), i32(y));
+            let weight = textureLoad(weights, offset, 0).r;
+            sum += weight * load_source(center + offset - half_size, size);
+        }
+    }
+
+    var color = saturate(sum / filter_args.divisor + vec4<f32>(filter_args.bias / 255.0));
+    if filter_args.preserve_alpha > 0u {
+        color.a = load_source(center, size).a;
+    }
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
@@ -0,0 +1,75 @@
+#import bevy_flash::common::srgb_to_linear
+
+struct Filter {
+    color: vec4<f32>,
+    strength: f32,
+    inner: u32,
+    knockout: u32,
+    composite_source: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> filter_args: Filter;
+@group(0) @binding(3) var blurred: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) source_uv: vec2<f32>,
+    @location(1) blur_uv: vec2<f32>,
+};
+
+struct VertexInput {
+    /// The position of the vertex in texture space (topleft 0,0, bottomright 1,1)
+    @location(0) position: vec2<f32>,
+
+    /// The coordinate of the source texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(1) source_uv: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(2) blur_uv: vec2<f32>,
+};
+
+@vertex
+fn vertex(in: VertexInput) -> VertexOutput {
+    // Convert texture space (topleft 0,0 to bottomright 1,1) to render space (topleft -1,1 to bottomright 1,-1)
+    let pos = vec4<f32>((in.position.x * 2.0 - 1.0), (1.0 - in.position.y * 2.0), 0.0, 1.0);
+    return VertexOutput(pos, in.source_uv, in.blur_uv);
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let inner = filter_args.inner > 0u;
+    let knockout = filter_args.knockout > 0u;
+    let composite_source = filter_args.composite_source > 0u;
+    var blur = textureSample(blurred, texture_sampler, in.blur_uv).a;
+    var dest = textureSample(texture, texture_sampler, in.source_uv);
+
+    if in.blur_uv.x < 0.0 || in.blur_uv.x > 1.0 || in.blur_uv.y < 0.0 || in.blur_uv.y > 1.0 {
+        blur = 0.0;
+    }
+
+    // 与发光滤镜一致，颜色需要先转换到线性空间
+    var color = srgb_to_linear(vec4<f32>(filter_args.color.r, filter_args.color.g, filter_args.color.b, 1.0));
+    if inner {
+        let alpha = filter_args.color.a * saturate((1.0 - blur) * filter_args.strength);
+        if knockout {
+            color = color * alpha * dest.a;
+        } else if composite_source {
+            color = color * alpha * dest.a + dest * (1.0 - alpha);
+        } else {
+            color = color * alpha * dest.a;
+        }
+    } else {
+        let alpha = filter_args.color.a * saturate(blur * filter_args.strength);
+        if knockout {
+            color = color * alpha * (1.0 - dest.a);
+        } else if composite_source {
+            color = color * alpha * (1.0 - dest.a) + dest;
+        } else {
+            color = color * alpha;
+        }
+    }
+
+    return color;
+}
@@ -0,0 +1,86 @@
+#import bevy_flash::common::srgb_to_linear
+
+struct Filter {
+    strength: f32,
+    filter_type: u32,
+    knockout: u32,
+    composite_source: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> filter_args: Filter;
+@group(0) @binding(3) var blurred: texture_2d<f32>;
+/// 256x1 的渐变色带，非预乘。索引 0 为高光，255 为阴影
+@group(0) @binding(4) var gradient: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) source_uv: vec2<f32>,
+    @location(1) blur_uv_left: vec2<f32>,
+    @location(2) blur_uv_right: vec2<f32>,
+};
+
+struct VertexInput {
+    /// The position of the vertex in texture space (topleft 0,0, bottomright 1,1)
+    @location(0) position: vec2<f32>,
+
+    /// The coordinate of the source texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(1) source_uv: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(2) blur_uv_left: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(3) blur_uv_right: vec2<f32>,
+};
+
+@vertex
+fn vertex(in: VertexInput) -> VertexOutput {
+    // Convert texture space (topleft 0,0 to bottomright 1,1) to render space (topleft -1,1 to bottomright 1,-1)
+    let pos = vec4<f32>((in.position.x * 2.0 - 1.0), (1.0 - in.position.y * 2.0), 0.0, 1.0);
+    return VertexOutput(pos, in.source_uv, in.blur_uv_left, in.blur_uv_right);
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let knockout = filter_args.knockout > 0u;
+    var blur_left = textureSample(blurred, texture_sampler, in.blur_uv_left).a;
+    var blur_right = textureSample(blurred, texture_sampler, in.blur_uv_right).a;
+    let dest = textureSample(texture, texture_sampler, in.source_uv);
+
+    let outer = filter_args.filter_type == 0u || filter_args.filter_type == 2u;
+    let inner = filter_args.filter_type == 1u || filter_args.filter_type == 2u;
+
+    if (in.blur_uv_left.x < 0.0 || in.blur_uv_left.x > 1.0 || in.blur_uv_left.y < 0.0 || in.blur_uv_left.y > 1.0) {
+        blur_left = 0.0;
+    }
+    if (in.blur_uv_right.x < 0.0 || in.blur_uv_right.x > 1.0 || in.blur_uv_right.y < 0.0 || in.blur_uv_right.y > 1.0) {
+        blur_right = 0.0;
+    }
+
+    // 高光一侧趋向索引 0，阴影一侧趋向索引 1，两侧相等时取色带中点
+    let index = saturate(0.5 + (blur_right - blur_left) * filter_args.strength * 0.5);
+    let ramp = textureSample(gradient, texture_sampler, vec2<f32>(index, 0.5));
+    let glow = srgb_to_linear(vec4<f32>(ramp.rgb, 1.0)) * ramp.a;
+
+    if (inner && outer) {
+        if (knockout) {
+            return glow;
+        } else {
+            return dest - dest * glow.a + glow;
+        }
+    } else if (inner) {
+        if (knockout) {
+            return glow * dest.a;
+        } else {
+            return glow * dest.a + dest * (1.0 - glow.a);
+        }
+    } else {
+        if (knockout) {
+            return glow - glow * dest.a;
+        } else {
+            return dest + glow - glow * dest.a;
+        }
+    }
+}
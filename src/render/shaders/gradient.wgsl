@@ -1,6 +1,4 @@
 #import bevy_flash::common::{
-    linear_to_srgb,
-    srgb_to_linear,
     get_world_from_local,
     mesh2d_position_local_to_world,
     mesh2d_position_world_to_clip,
@@ -86,10 +84,10 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
         // Repeat
         t = fract(t);
     }
-    var color = textureSample(texture, texture_sampler, vec2<f32>(t, 0.0));
-    if gradient.interpolation != 0 {
-        color = linear_to_srgb(color);
-    }
+    // 颜色表首尾像素对应比例 0 与 1，采样像素中心避免两端与相邻像素混合。
+    // 线性 RGB 插值已在生成颜色表时完成，sRGB 纹理采样即得到线性颜色
+    let size = f32(textureDimensions(texture).x);
+    let color = textureSample(texture, texture_sampler, vec2<f32>((t * (size - 1.0) + 0.5) / size, 0.0));
     let out = saturate(part_mesh2d_color_transform(in.instance_index, color));
     let alpha = saturate(out.a);
     return vec4<f32>(out.rgb * alpha, alpha);
@@ -0,0 +1,13 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var mask: texture_2d<f32>;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(texture, texture_sampler, in.uv);
+    // 颜色是预乘 alpha 的，直接整体乘以遮罩覆盖率
+    let coverage = textureSample(mask, texture_sampler, in.uv).a;
+    return color * coverage;
+}
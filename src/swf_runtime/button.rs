@@ -0,0 +1,527 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::{Values, ValuesMut};
+use std::sync::Arc;
+
+use bevy::log::{error_once, warn_once};
+use bevy::platform::collections::HashMap;
+use swf::{ButtonRecord, CharacterId, Depth, Point, Rectangle, Twips};
+
+use crate::RenderContext;
+
+use super::character::Character;
+use super::display_object::{DisplayObject, DisplayObjectBase, TDisplayObject};
+use super::filter::Filter;
+use super::matrix::Matrix;
+use super::shape_utils::HitPath;
+use super::tag_utils::SwfMovie;
+
+/// 按钮当前显示的状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ButtonState {
+    #[default]
+    Up,
+    Over,
+    Down,
+}
+
+impl ButtonState {
+    fn record_flag(self) -> swf::ButtonState {
+        match self {
+            ButtonState::Up => swf::ButtonState::UP,
+            ButtonState::Over => swf::ButtonState::OVER,
+            ButtonState::Down => swf::ButtonState::DOWN,
+        }
+    }
+}
+
+/// 鼠标左键的输入状态
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PointerInput {
+    pub(crate) pressed: bool,
+    pub(crate) just_pressed: bool,
+    pub(crate) just_released: bool,
+}
+
+/// 遍历显示列表更新按钮时的状态
+#[derive(Debug, Default)]
+pub(crate) struct PointerContext {
+    pub(crate) input: PointerInput,
+    /// 根显示列表是否翻转，与渲染时一致
+    pub(crate) flip_x: bool,
+    pub(crate) flip_y: bool,
+    /// 是否已经有按钮被命中，指针只作用于最上层命中的按钮
+    pub(crate) hit: bool,
+    /// 当前遍历到的实例路径
+    pub(crate) path: Vec<String>,
+    /// 被点击的按钮实例路径
+    pub(crate) clicked: Vec<String>,
+}
+
+/// 按钮命中区域中的一个图形
+#[derive(Debug, Clone)]
+pub(crate) struct HitArea {
+    /// 图形到按钮坐标系的变换
+    matrix: Matrix,
+    /// 图形坐标系下的边界框
+    bounds: Rectangle<Twips>,
+    /// 形状的填充与线条，为 `None` 时（例如文本）只检测边界框
+    paths: Option<Arc<Vec<HitPath>>>,
+}
+
+impl HitArea {
+    pub(crate) fn new(
+        matrix: Matrix,
+        bounds: Rectangle<Twips>,
+        paths: Option<Arc<Vec<HitPath>>>,
+    ) -> Self {
+        Self {
+            matrix,
+            bounds,
+            paths,
+        }
+    }
+
+    /// 嵌套在其他图形中时，叠加父级的变换
+    pub(crate) fn transformed(&self, matrix: Matrix) -> Self {
+        Self {
+            matrix: matrix * self.matrix,
+            ..self.clone()
+        }
+    }
+
+    /// `point` 为按钮坐标系下的位置，转换到图形坐标系后按填充规则检测
+    fn hit_test(&self, point: Point<Twips>) -> bool {
+        let Some(inverse) = self.matrix.inverse() else {
+            return false;
+        };
+        let point = inverse * point;
+        let bounds = &self.bounds;
+        if point.x < bounds.x_min
+            || point.x > bounds.x_max
+            || point.y < bounds.y_min
+            || point.y > bounds.y_max
+        {
+            return false;
+        }
+        self.paths
+            .as_ref()
+            .is_none_or(|paths| paths.iter().any(|path| path.hit_test(point)))
+    }
+}
+
+/// 按钮（DefineButton / DefineButton2）
+#[derive(Debug, Clone)]
+pub struct Button {
+    id: CharacterId,
+    base: DisplayObjectBase,
+    records: Arc<Vec<ButtonRecord>>,
+    /// 命中状态的图形
+    hit_areas: Arc<Vec<HitArea>>,
+    state: ButtonState,
+    /// 当前状态的子对象是否已经创建
+    constructed: bool,
+    /// 指针在按钮上按下，松开前一直跟踪
+    tracking: bool,
+    depth_list: BTreeMap<Depth, DisplayObject>,
+    movie: Arc<SwfMovie>,
+}
+
+impl Button {
+    /// 命中状态的图形从已经定义的字符中收集，没有命中区域的按钮无法被点击
+    pub fn from_swf_tag(
+        button: swf::Button,
+        characters: &HashMap<CharacterId, Character>,
+        movie: Arc<SwfMovie>,
+    ) -> Self {
+        let mut hit_areas = Vec::new();
+        for record in button
+            .records
+            .iter()
+            .filter(|record| record.states.contains(swf::ButtonState::HIT_TEST))
+        {
+            if let Some(character) = characters.get(&record.id) {
+                character.hit_areas(characters, record.matrix.into(), &mut hit_areas);
+            }
+        }
+        if hit_areas.is_empty() {
+            warn_once!("Button {} has no hit area and can't be clicked", button.id);
+        }
+        Self {
+            id: button.id,
+            base: Default::default(),
+            records: Arc::new(button.records),
+            hit_areas: Arc::new(hit_areas),
+            state: ButtonState::Up,
+            constructed: false,
+            tracking: false,
+            depth_list: BTreeMap::new(),
+            movie,
+        }
+    }
+
+    pub fn state(&self) -> ButtonState {
+        self.state
+    }
+
+    /// 命中区域在按钮坐标系下的边界框
+    pub fn hit_bounds(&self) -> Option<Rectangle<Twips>> {
+        self.hit_areas
+            .iter()
+            .map(|area| area.matrix * area.bounds.clone())
+            .reduce(|bounds, hit_bounds| bounds.union(&hit_bounds))
+    }
+
+    /// 命中状态的图形
+    pub(crate) fn hit_areas(&self) -> &[HitArea] {
+        &self.hit_areas
+    }
+
+    /// `point` 为按钮坐标系下的位置，形状按填充与线条的路径检测，而不是边界框
+    pub fn hit_test(&self, point: Point<Twips>) -> bool {
+        self.hit_areas.iter().any(|area| area.hit_test(point))
+    }
+
+    /// 切换状态并重新创建子对象，两个状态共有的子对象会被保留，以免影片剪辑从头播放
+    fn set_state(&mut self, characters: &HashMap<CharacterId, Character>, state: ButtonState) {
+        self.state = state;
+        self.constructed = true;
+        let mut previous = std::mem::take(&mut self.depth_list);
+        for record in self
+            .records
+            .iter()
+            .filter(|record| record.states.contains(state.record_flag()))
+        {
+            let child = match previous.remove(&record.depth) {
+                Some(child) if child.id() == record.id => Some(child),
+                _ => instantiate_record(record, characters),
+            };
+            if let Some(mut child) = child {
+                child.set_matrix(record.matrix.into());
+                child.set_color_transform(record.color_transform);
+                self.depth_list.insert(record.depth, child);
+            }
+        }
+    }
+
+    /// 根据指针输入更新状态，`position` 为按钮坐标系下的指针位置，
+    /// 被上层的按钮遮挡时为 `None`。返回状态是否改变以及是否被点击
+    pub(crate) fn handle_pointer(
+        &mut self,
+        characters: &HashMap<CharacterId, Character>,
+        position: Option<Point<Twips>>,
+        input: &PointerInput,
+    ) -> (bool, bool) {
+        let hovered = position.is_some_and(|point| self.hit_test(point));
+        if hovered && input.just_pressed {
+            self.tracking = true;
+        }
+        let clicked = hovered && self.tracking && input.just_released;
+        if !input.pressed {
+            self.tracking = false;
+        }
+        let state = match (hovered, self.tracking) {
+            (true, true) => ButtonState::Down,
+            (true, false) if !input.pressed => ButtonState::Over,
+            _ => ButtonState::Up,
+        };
+        let changed = state != self.state;
+        if changed {
+            self.set_state(characters, state);
+        }
+        (changed, clicked)
+    }
+
+    pub fn render_list(&self) -> Values<'_, Depth, DisplayObject> {
+        self.depth_list.values()
+    }
+
+    pub fn render_list_mut(&mut self) -> ValuesMut<'_, Depth, DisplayObject> {
+        self.depth_list.values_mut()
+    }
+}
+
+/// 按按钮记录创建子对象并运行第一帧
+fn instantiate_record(
+    record: &ButtonRecord,
+    characters: &HashMap<CharacterId, Character>,
+) -> Option<DisplayObject> {
+    let Some(character) = characters.get(&record.id).cloned() else {
+        error_once!("Unable to instantiate button record id {}", record.id);
+        return None;
+    };
+    let mut child: DisplayObject = character.into();
+    child.set_depth(record.depth);
+    child.set_blend_mode(record.blend_mode);
+    child.set_filters(record.filters.iter().map(Filter::from).collect());
+    child.enter_frame(characters);
+    Some(child)
+}
+
+impl TDisplayObject for Button {
+    fn base(&self) -> &DisplayObjectBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut DisplayObjectBase {
+        &mut self.base
+    }
+
+    fn movie(&self) -> Arc<SwfMovie> {
+        self.movie.clone()
+    }
+
+    fn enter_frame(&mut self, characters: &HashMap<CharacterId, Character>) {
+        if !self.constructed {
+            self.set_state(characters, self.state);
+            return;
+        }
+        for child in self.depth_list.values_mut().rev() {
+            child.enter_frame(characters);
+        }
+    }
+
+    fn replace_with(&mut self, id: CharacterId, characters: &HashMap<CharacterId, Character>) {
+        if let Some(Character::Button(button)) = characters.get(&id) {
+            self.id = button.id;
+            self.records = button.records.clone();
+            self.hit_areas = button.hit_areas.clone();
+            self.movie = button.movie.clone();
+            self.set_state(characters, self.state);
+        }
+    }
+
+    fn self_bounds(&mut self, _context: &mut RenderContext) -> Rectangle<Twips> {
+        Default::default()
+    }
+
+    fn children_mut(&mut self) -> Option<ValuesMut<'_, Depth, DisplayObject>> {
+        Some(self.render_list_mut())
+    }
+
+    fn id(&self) -> CharacterId {
+        self.id
+    }
+
+    fn allow_as_mask(&self) -> bool {
+        !self.depth_list.is_empty()
+    }
+}
+
+impl From<Button> for DisplayObject {
+    fn from(button: Button) -> Self {
+        Self::Button(button)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::graphic::Graphic;
+    use super::super::shape_utils::calculate_shape_bounds;
+    use super::*;
+
+    /// 只有文件头与结束标签的 SWF
+    const EMPTY_SWF: [u8; 15] = [b'F', b'W', b'S', 10, 15, 0, 0, 0, 0, 0, 24, 1, 0, 0, 0];
+
+    /// 以纯色填充的多边形
+    fn polygon(id: CharacterId, points: &[(f64, f64)], movie: Arc<SwfMovie>) -> Character {
+        let twips = |(x, y): (f64, f64)| Point::new(Twips::from_pixels(x), Twips::from_pixels(y));
+        let mut records = vec![swf::ShapeRecord::StyleChange(Box::new(
+            swf::StyleChangeData {
+                move_to: Some(twips(points[0])),
+                fill_style_0: None,
+                fill_style_1: Some(1),
+                line_style: None,
+                new_styles: None,
+            },
+        ))];
+        for (index, &(x, y)) in points.iter().enumerate() {
+            let (next_x, next_y) = points[(index + 1) % points.len()];
+            records.push(swf::ShapeRecord::StraightEdge {
+                delta: swf::PointDelta::new(
+                    Twips::from_pixels(next_x - x),
+                    Twips::from_pixels(next_y - y),
+                ),
+            });
+        }
+        let bounds = calculate_shape_bounds(&records);
+        let shape = swf::Shape {
+            version: 1,
+            id,
+            shape_bounds: bounds.clone(),
+            edge_bounds: bounds,
+            flags: swf::ShapeFlag::empty(),
+            styles: swf::ShapeStyles {
+                fill_styles: vec![swf::FillStyle::Color(swf::Color::from_rgba(0xff000000))],
+                line_styles: vec![],
+            },
+            shape: records,
+        };
+        Character::Graphic(Graphic::from_swf_tag(shape, movie))
+    }
+
+    /// 按钮的唯一记录是向右平移 5 像素的多边形
+    fn button_with_shape(
+        states: swf::ButtonState,
+        points: &[(f64, f64)],
+    ) -> (Button, HashMap<CharacterId, Character>) {
+        let movie = Arc::new(SwfMovie::from_data(&EMPTY_SWF).unwrap());
+        let mut characters = HashMap::new();
+        characters.insert(1, polygon(1, points, movie.clone()));
+        let record = ButtonRecord {
+            states,
+            id: 1,
+            depth: 1,
+            matrix: Matrix::translate(Twips::from_pixels(5.0), Twips::ZERO).into(),
+            color_transform: Default::default(),
+            filters: vec![],
+            blend_mode: swf::BlendMode::Normal,
+        };
+        let tag = swf::Button {
+            id: 2,
+            is_track_as_menu: false,
+            records: vec![record],
+            actions: vec![],
+        };
+        (Button::from_swf_tag(tag, &characters, movie), characters)
+    }
+
+    /// 10x10 像素的正方形按钮
+    fn button(states: swf::ButtonState) -> (Button, HashMap<CharacterId, Character>) {
+        button_with_shape(
+            states,
+            &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+        )
+    }
+
+    fn point(x: f64, y: f64) -> Point<Twips> {
+        Point::new(Twips::from_pixels(x), Twips::from_pixels(y))
+    }
+
+    #[test]
+    fn hit_test_uses_transformed_hit_state_bounds() {
+        let (button, _) = button(swf::ButtonState::all());
+        assert!(button.hit_test(point(6.0, 5.0)));
+        assert!(!button.hit_test(point(2.0, 5.0)));
+        assert!(!button.hit_test(point(16.0, 5.0)));
+    }
+
+    #[test]
+    fn hit_test_follows_the_shape_outline() {
+        let (button, _) = button_with_shape(
+            swf::ButtonState::all(),
+            &[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)],
+        );
+        assert!(button.hit_test(point(7.0, 2.0)));
+        // 在边界框内但在三角形外
+        assert!(!button.hit_test(point(13.0, 8.0)));
+        assert_eq!(
+            button.hit_bounds(),
+            Some(Rectangle {
+                x_min: Twips::from_pixels(5.0),
+                x_max: Twips::from_pixels(15.0),
+                y_min: Twips::ZERO,
+                y_max: Twips::from_pixels(10.0),
+            })
+        );
+    }
+
+    #[test]
+    fn hit_test_applies_the_fill_rule_to_holes() {
+        // 外框与反向的内框组成的环形
+        let (button, _) = button_with_shape(
+            swf::ButtonState::all(),
+            &[
+                (0.0, 0.0),
+                (10.0, 0.0),
+                (10.0, 10.0),
+                (0.0, 10.0),
+                (0.0, 0.0),
+                (3.0, 3.0),
+                (3.0, 7.0),
+                (7.0, 7.0),
+                (7.0, 3.0),
+                (3.0, 3.0),
+            ],
+        );
+        assert!(button.hit_test(point(6.0, 5.0)));
+        assert!(!button.hit_test(point(10.0, 5.0)));
+    }
+
+    #[test]
+    fn records_without_hit_state_are_not_hit() {
+        let (button, _) = button(swf::ButtonState::UP | swf::ButtonState::OVER);
+        assert!(!button.hit_test(point(6.0, 5.0)));
+    }
+
+    #[test]
+    fn nested_hit_characters_contribute_bounds() {
+        let (inner, mut characters) = button(swf::ButtonState::all());
+        characters.insert(2, Character::Button(inner));
+        let record = ButtonRecord {
+            states: swf::ButtonState::HIT_TEST,
+            id: 2,
+            depth: 1,
+            matrix: Matrix::translate(Twips::from_pixels(5.0), Twips::ZERO).into(),
+            color_transform: Default::default(),
+            filters: vec![],
+            blend_mode: swf::BlendMode::Normal,
+        };
+        let tag = swf::Button {
+            id: 3,
+            is_track_as_menu: false,
+            records: vec![record],
+            actions: vec![],
+        };
+        let movie = Arc::new(SwfMovie::from_data(&EMPTY_SWF).unwrap());
+        let button = Button::from_swf_tag(tag, &characters, movie);
+        assert!(button.hit_test(point(12.0, 5.0)));
+        assert!(!button.hit_test(point(8.0, 5.0)));
+    }
+
+    #[test]
+    fn click_requires_press_and_release_over_button() {
+        let (mut button, characters) = button(swf::ButtonState::all());
+        let over = Some(point(6.0, 5.0));
+        let mut input = PointerInput::default();
+
+        assert_eq!(
+            button.handle_pointer(&characters, over, &input),
+            (true, false)
+        );
+        assert_eq!(button.state(), ButtonState::Over);
+
+        input.pressed = true;
+        input.just_pressed = true;
+        assert_eq!(
+            button.handle_pointer(&characters, over, &input),
+            (true, false)
+        );
+        assert_eq!(button.state(), ButtonState::Down);
+
+        input.pressed = false;
+        input.just_pressed = false;
+        input.just_released = true;
+        assert_eq!(
+            button.handle_pointer(&characters, over, &input),
+            (true, true)
+        );
+        assert_eq!(button.state(), ButtonState::Over);
+
+        // 在按钮外按下再移入松开不算点击
+        input = PointerInput {
+            pressed: true,
+            just_pressed: true,
+            ..Default::default()
+        };
+        button.handle_pointer(&characters, None, &input);
+        assert_eq!(button.state(), ButtonState::Up);
+        input = PointerInput {
+            just_released: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            button.handle_pointer(&characters, over, &input),
+            (true, false)
+        );
+    }
+}
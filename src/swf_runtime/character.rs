@@ -4,19 +4,79 @@ use bevy::log::error_once;
 use bevy::platform::collections::HashMap;
 use swf::{CharacterId, DefineBitsLossless, PlaceObject};
 
+use super::button::{Button, HitArea};
 use super::decoder::error::Error;
 use super::decoder::{Bitmap, decode_define_bits_jpeg, decode_define_bits_lossless};
 use super::display_object::{DisplayObject, FrameNumber, TDisplayObject};
+use super::edit_text::EditText;
 use super::graphic::Graphic;
+use super::matrix::Matrix;
 use super::morph_shape::MorphShape;
 use super::movie_clip::MovieClip;
+use super::shape_utils::HitPath;
 use super::tag_utils::{SwfMovie, SwfSlice};
+use super::text::Text;
 
 #[derive(Clone)]
 pub enum Character {
     MovieClip(MovieClip),
     Graphic(Graphic),
     MorphShape(MorphShape),
+    Text(Text),
+    EditText(EditText),
+    Button(Button),
+}
+
+impl Character {
+    /// 收集用于按钮命中检测的图形，`matrix` 为该字符到按钮坐标系的变换。
+    ///
+    /// 形状按填充与线条检测，变形形状取起始形状，影片剪辑取第一帧的内容，按钮取命中状态的图形，
+    /// 文本只检测边界框
+    pub(crate) fn hit_areas(
+        &self,
+        characters: &HashMap<CharacterId, Character>,
+        matrix: Matrix,
+        areas: &mut Vec<HitArea>,
+    ) {
+        match self {
+            Character::Graphic(graphic) => areas.push(HitArea::new(
+                matrix,
+                graphic.bounds().clone(),
+                Some(Arc::new(HitPath::from_shape(graphic.shape()))),
+            )),
+            Character::MorphShape(morph_shape) => {
+                let shape = morph_shape.shape(0);
+                areas.push(HitArea::new(
+                    matrix,
+                    shape.shape_bounds.clone(),
+                    Some(Arc::new(HitPath::from_shape(&shape))),
+                ));
+            }
+            Character::Text(text) => {
+                areas.push(HitArea::new(matrix, text.bounds().clone(), None));
+            }
+            Character::EditText(edit_text) => {
+                areas.push(HitArea::new(matrix, edit_text.bounds().clone(), None));
+            }
+            Character::Button(button) => {
+                areas.extend(
+                    button
+                        .hit_areas()
+                        .iter()
+                        .map(|area| area.transformed(matrix)),
+                );
+            }
+            Character::MovieClip(movie_clip) => {
+                let mut movie_clip = movie_clip.clone();
+                movie_clip.enter_frame(characters);
+                for child in movie_clip.render_list() {
+                    if let Some(character) = characters.get(&child.id()) {
+                        character.hit_areas(characters, matrix * *child.matrix(), areas);
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl From<Character> for DisplayObject {
@@ -25,6 +85,9 @@ impl From<Character> for DisplayObject {
             Character::MovieClip(movie_clip) => DisplayObject::MovieClip(movie_clip),
             Character::Graphic(graphic) => DisplayObject::Graphic(graphic),
             Character::MorphShape(morph_shape) => DisplayObject::MorphShape(morph_shape),
+            Character::Text(text) => DisplayObject::Text(text),
+            Character::EditText(edit_text) => DisplayObject::EditText(edit_text),
+            Character::Button(button) => DisplayObject::Button(button),
         }
     }
 }
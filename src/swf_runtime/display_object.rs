@@ -4,18 +4,26 @@ use std::sync::Arc;
 use bevy::{
     asset::{Assets, Handle, RenderAssetUsages},
     image::Image,
-    log::warn_once,
     math::{IVec2, UVec2},
     platform::collections::HashMap,
     render::render_resource::TextureFormat,
 };
-use swf::{BlendMode, CharacterId, ColorTransform, Depth, Rectangle, Twips};
+use swf::{BlendMode, CharacterId, Color, ColorTransform, Depth, Rectangle, Twips};
 
 use crate::RenderContext;
 
 use super::{
-    character::Character, filter::Filter, graphic::Graphic, matrix::Matrix,
-    morph_shape::MorphShape, movie_clip::MovieClip, tag_utils::SwfMovie, transform::Transform,
+    button::Button,
+    character::Character,
+    edit_text::EditText,
+    filter::{DisplacementMapFilter, Filter},
+    graphic::Graphic,
+    matrix::Matrix,
+    morph_shape::MorphShape,
+    movie_clip::MovieClip,
+    tag_utils::SwfMovie,
+    text::Text,
+    transform::Transform,
 };
 
 pub(crate) type FrameNumber = u16;
@@ -159,7 +167,7 @@ impl ImageCache {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct DisplayObjectBase {
     name: Option<Box<str>>,
     place_frame: FrameNumber,
@@ -167,10 +175,36 @@ pub struct DisplayObjectBase {
     clip_depth: Depth,
     transform: Transform,
     filters: Vec<Filter>,
+    /// 运行时附加的置换图滤镜，置换图滤镜不会出现在 SWF 标签中，时间轴更新滤镜时保留
+    displacement_map: Option<DisplacementMapFilter>,
     blend_mode: BlendMode,
     as_bitmap_cached: bool,
     cache_dirty: bool,
+    /// 不可见的对象及其子对象不参与渲染和边界计算，但仍会继续播放
+    visible: bool,
+    /// 位图缓存时使用的不透明背景色
+    background_color: Option<Color>,
 }
+
+impl Default for DisplayObjectBase {
+    fn default() -> Self {
+        Self {
+            name: None,
+            place_frame: Default::default(),
+            depth: Default::default(),
+            clip_depth: Default::default(),
+            transform: Default::default(),
+            filters: Default::default(),
+            displacement_map: None,
+            blend_mode: Default::default(),
+            as_bitmap_cached: false,
+            cache_dirty: false,
+            visible: true,
+            background_color: None,
+        }
+    }
+}
+
 impl DisplayObjectBase {
     fn set_matrix(&mut self, matrix: Matrix) {
         self.transform.matrix = matrix;
@@ -193,18 +227,35 @@ impl DisplayObjectBase {
         }
     }
 
+    fn set_displacement_map(&mut self, displacement_map: Option<DisplacementMapFilter>) -> bool {
+        if displacement_map != self.displacement_map {
+            self.displacement_map = displacement_map;
+            true
+        } else {
+            false
+        }
+    }
+
     fn recheck_cache(
         &self,
         shape_depth_layer: &str,
         image_caches: &mut HashMap<String, ImageCache>,
     ) {
-        if !self.filters.is_empty() && image_caches.get(shape_depth_layer).is_none()
-            || self.as_bitmap_cached
-        {
+        let has_filters = !self.filters.is_empty() || self.displacement_map.is_some();
+        if has_filters && image_caches.get(shape_depth_layer).is_none() || self.as_bitmap_cached {
             image_caches.insert(shape_depth_layer.to_owned(), ImageCache::default());
         }
     }
 
+    fn set_background_color(&mut self, background_color: Option<Color>) -> bool {
+        if background_color != self.background_color {
+            self.background_color = background_color;
+            true
+        } else {
+            false
+        }
+    }
+
     fn invalidate_cached_bitmap(&mut self) {
         self.cache_dirty = true;
     }
@@ -213,17 +264,36 @@ impl DisplayObjectBase {
         self.name = name;
     }
 
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     pub fn transform(&self) -> &Transform {
         &self.transform
     }
 
+    /// 时间轴上的滤镜，之后是运行时附加的置换图滤镜
     pub fn filters(&self) -> Vec<Filter> {
-        self.filters.clone()
+        let mut filters = self.filters.clone();
+        if let Some(displacement_map) = &self.displacement_map {
+            filters.push(Filter::DisplacementMapFilter(displacement_map.clone()));
+        }
+        filters
     }
 
     pub fn blend_mode(&self) -> BlendMode {
         self.blend_mode
     }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// 位图缓存的不透明背景色，透明的背景色等同于没有背景
+    pub fn opaque_background(&self) -> Option<Color> {
+        self.background_color
+            .filter(|color| self.as_bitmap_cached && color.a > 0)
+    }
 }
 
 pub(crate) trait TDisplayObject: Clone + Into<DisplayObject> {
@@ -260,6 +330,22 @@ pub(crate) trait TDisplayObject: Clone + Into<DisplayObject> {
         }
     }
 
+    fn set_displacement_map(&mut self, displacement_map: Option<DisplacementMapFilter>) {
+        if self.base_mut().set_displacement_map(displacement_map) {
+            self.invalidate_cached_bitmap();
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.base_mut().visible = visible;
+    }
+
+    fn set_background_color(&mut self, background_color: Option<Color>) {
+        if self.base_mut().set_background_color(background_color) {
+            self.invalidate_cached_bitmap();
+        }
+    }
+
     fn invalidate_cached_bitmap(&mut self) {
         self.base_mut().invalidate_cached_bitmap();
     }
@@ -284,6 +370,11 @@ pub(crate) trait TDisplayObject: Clone + Into<DisplayObject> {
         self.base().depth
     }
 
+    /// 实例名称
+    fn name(&self) -> Option<&str> {
+        self.base().name()
+    }
+
     fn clip_depth(&self) -> Depth {
         self.base().clip_depth
     }
@@ -300,6 +391,14 @@ pub(crate) trait TDisplayObject: Clone + Into<DisplayObject> {
         self.base().blend_mode()
     }
 
+    fn visible(&self) -> bool {
+        self.base().visible()
+    }
+
+    fn opaque_background(&self) -> Option<Color> {
+        self.base().opaque_background()
+    }
+
     fn matrix(&self) -> &Matrix {
         &self.base().transform().matrix
     }
@@ -332,7 +431,7 @@ pub(crate) trait TDisplayObject: Clone + Into<DisplayObject> {
         let scale = context.scale;
         let mut bounds = *matrix * self.self_bounds(context);
         if let Some(children) = self.children_mut() {
-            for child in children {
+            for child in children.filter(|child| child.visible()) {
                 let matrix = *matrix * *child.matrix();
                 bounds = bounds.union(&child.render_bounds_with_transform(&matrix, true, context));
             }
@@ -370,11 +469,12 @@ pub(crate) trait TDisplayObject: Clone + Into<DisplayObject> {
             self.base_mut().as_bitmap_cached = is_bitmap_cached;
         }
         if version >= 11 {
-            if let Some(_visible) = place_object.is_visible {
-                //TODO:
-                warn_once!("visible is not supported. id: {}. TODO!", self.id());
+            if let Some(visible) = place_object.is_visible {
+                self.set_visible(visible);
+            }
+            if let Some(color) = place_object.background_color {
+                self.set_background_color(Some(color));
             }
-            if let Some(_color) = place_object.background_color {}
         }
         if let Some(filters) = &place_object.filters {
             self.set_filters(filters.iter().map(Filter::from).collect())
@@ -405,6 +505,9 @@ pub enum DisplayObject {
     Graphic(Graphic),
     MovieClip(MovieClip),
     MorphShape(MorphShape),
+    Text(Text),
+    EditText(EditText),
+    Button(Button),
 }
 
 impl TDisplayObject for DisplayObject {
@@ -413,6 +516,9 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.base(),
             Self::MovieClip(m) => m.base(),
             Self::MorphShape(m) => m.base(),
+            Self::Text(t) => t.base(),
+            Self::EditText(t) => t.base(),
+            Self::Button(b) => b.base(),
         }
     }
 
@@ -421,6 +527,9 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.base_mut(),
             Self::MovieClip(m) => m.base_mut(),
             Self::MorphShape(m) => m.base_mut(),
+            Self::Text(t) => t.base_mut(),
+            Self::EditText(t) => t.base_mut(),
+            Self::Button(b) => b.base_mut(),
         }
     }
 
@@ -429,6 +538,9 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.movie(),
             Self::MovieClip(m) => m.movie(),
             Self::MorphShape(m) => m.movie(),
+            Self::Text(t) => t.movie(),
+            Self::EditText(t) => t.movie(),
+            Self::Button(b) => b.movie(),
         }
     }
 
@@ -437,6 +549,9 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.enter_frame(characters),
             Self::MovieClip(m) => m.enter_frame(characters),
             Self::MorphShape(m) => m.enter_frame(characters),
+            Self::Text(t) => t.enter_frame(characters),
+            Self::EditText(t) => t.enter_frame(characters),
+            Self::Button(b) => b.enter_frame(characters),
         }
     }
 
@@ -445,6 +560,9 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.replace_with(id, characters),
             Self::MovieClip(m) => m.replace_with(id, characters),
             Self::MorphShape(m) => m.replace_with(id, characters),
+            Self::Text(t) => t.replace_with(id, characters),
+            Self::EditText(t) => t.replace_with(id, characters),
+            Self::Button(b) => b.replace_with(id, characters),
         }
     }
 
@@ -453,12 +571,16 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.self_bounds(context),
             Self::MovieClip(m) => m.self_bounds(context),
             Self::MorphShape(m) => m.self_bounds(context),
+            Self::Text(t) => t.self_bounds(context),
+            Self::EditText(t) => t.self_bounds(context),
+            Self::Button(b) => b.self_bounds(context),
         }
     }
 
     fn children_mut(&mut self) -> Option<ValuesMut<'_, u16, DisplayObject>> {
         match self {
             Self::MovieClip(m) => m.children_mut(),
+            Self::Button(b) => b.children_mut(),
             _ => None,
         }
     }
@@ -468,6 +590,9 @@ impl TDisplayObject for DisplayObject {
             Self::Graphic(g) => g.id(),
             Self::MovieClip(m) => m.id(),
             Self::MorphShape(m) => m.id(),
+            Self::Text(t) => t.id(),
+            Self::EditText(t) => t.id(),
+            Self::Button(b) => b.id(),
         }
     }
 
@@ -478,3 +603,57 @@ impl TDisplayObject for DisplayObject {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swf_runtime::filter::DisplacementMapFilterMode;
+
+    fn displacement_map() -> DisplacementMapFilter {
+        DisplacementMapFilter {
+            map_bitmap: Handle::default(),
+            map_point: (0, 0),
+            component_x: 1,
+            component_y: 2,
+            scale_x: 10.0,
+            scale_y: 10.0,
+            mode: DisplacementMapFilterMode::Clamp,
+            color: Color::from_rgba(0),
+            viewscale_x: 1.0,
+            viewscale_y: 1.0,
+        }
+    }
+
+    #[test]
+    fn displacement_map_follows_timeline_filters() {
+        let mut base = DisplayObjectBase::default();
+        assert!(base.set_displacement_map(Some(displacement_map())));
+        assert!(!base.set_displacement_map(Some(displacement_map())));
+
+        let blur = Filter::BlurFilter(swf::BlurFilter {
+            blur_x: swf::Fixed16::from_f32(1.0),
+            blur_y: swf::Fixed16::from_f32(1.0),
+            flags: swf::BlurFilterFlags::from_passes(1),
+        });
+        base.set_filters(vec![blur.clone()]);
+        assert_eq!(
+            base.filters(),
+            vec![blur, Filter::DisplacementMapFilter(displacement_map())]
+        );
+
+        assert!(base.set_displacement_map(None));
+        assert_eq!(base.filters().len(), 1);
+    }
+
+    #[test]
+    fn displacement_map_enables_image_cache() {
+        let mut base = DisplayObjectBase::default();
+        let mut image_caches = HashMap::new();
+        base.recheck_cache("1", &mut image_caches);
+        assert!(image_caches.is_empty());
+
+        base.set_displacement_map(Some(displacement_map()));
+        base.recheck_cache("1", &mut image_caches);
+        assert!(image_caches.contains_key("1"));
+    }
+}
@@ -0,0 +1,489 @@
+use std::sync::Arc;
+
+use bevy::platform::collections::HashMap;
+use swf::{
+    BlendMode, CharacterId, Color, ColorTransform, EditTextFlag, Rectangle, TextAlign, Twips,
+};
+
+use crate::RenderContext;
+
+use super::character::Character;
+use super::display_object::{DisplayObject, DisplayObjectBase, TDisplayObject};
+use super::matrix::Matrix;
+use super::tag_utils::SwfMovie;
+use super::text::{Font, GlyphShapes, TextGlyph, render_glyphs};
+use super::transform::Transform;
+
+/// 文本框四周 2 像素的留白
+const GUTTER: Twips = Twips::new(40);
+
+/// 使用设备字体（Bevy 字体）绘制的文本，由 `FlashPlugin` 生成 `Text2d` 子实体显示
+#[derive(Debug, Clone)]
+pub(crate) struct DeviceText {
+    pub(crate) text: String,
+    /// 文本左上角的变换
+    pub(crate) matrix: Matrix,
+    pub(crate) font_size: f32,
+    pub(crate) color: Color,
+    /// 自动换行的宽度（像素）
+    pub(crate) width: Option<f32>,
+    pub(crate) align: TextAlign,
+}
+
+/// 带颜色的字符，由纯文本或 HTML 文本解析得到
+type StyledChar = (char, Color);
+
+/// 一行中的字形
+struct LineGlyph {
+    index: Option<u32>,
+    color: Color,
+    advance: Twips,
+    is_space: bool,
+}
+
+/// 动态文本（DefineEditText），可以在运行时通过实例名称设置文本
+#[derive(Debug, Clone)]
+pub struct EditText {
+    id: CharacterId,
+    base: DisplayObjectBase,
+    /// 文本框定义的边界
+    field_bounds: Rectangle<Twips>,
+    /// 排版后的边界，自动大小时会根据文本调整
+    bounds: Rectangle<Twips>,
+    font: Option<Arc<Font>>,
+    font_id: CharacterId,
+    glyph_shapes: Arc<GlyphShapes>,
+    height: Twips,
+    color: Color,
+    align: TextAlign,
+    left_margin: Twips,
+    right_margin: Twips,
+    indent: Twips,
+    leading: Twips,
+    flags: EditTextFlag,
+    /// 原始文本，HTML 文本框中为 HTML
+    text: String,
+    glyphs: Vec<TextGlyph>,
+    movie: Arc<SwfMovie>,
+}
+
+impl EditText {
+    pub fn from_swf_tag(
+        edit_text: swf::EditText,
+        fonts: &HashMap<CharacterId, Arc<Font>>,
+        movie: Arc<SwfMovie>,
+    ) -> Self {
+        let font_id = edit_text.font_id().unwrap_or_default();
+        let layout = edit_text.layout();
+        let text = edit_text
+            .initial_text()
+            .map(|text| text.to_str_lossy(movie.encoding()).into_owned())
+            .unwrap_or_default();
+        let mut edit_text = Self {
+            id: edit_text.id(),
+            base: Default::default(),
+            field_bounds: edit_text.bounds().clone(),
+            bounds: edit_text.bounds().clone(),
+            font: fonts.get(&font_id).cloned(),
+            font_id,
+            glyph_shapes: Arc::default(),
+            height: edit_text.height().unwrap_or(Twips::new(240)),
+            color: edit_text.color().cloned().unwrap_or(Color::BLACK),
+            align: layout.map_or(TextAlign::Left, |layout| layout.align),
+            left_margin: layout.map_or(Twips::ZERO, |layout| layout.left_margin),
+            right_margin: layout.map_or(Twips::ZERO, |layout| layout.right_margin),
+            indent: layout.map_or(Twips::ZERO, |layout| layout.indent),
+            leading: layout.map_or(Twips::ZERO, |layout| layout.leading),
+            flags: edit_text.flags(),
+            text,
+            glyphs: Vec::new(),
+            movie,
+        };
+        edit_text.relayout();
+        edit_text
+    }
+
+    /// 使用嵌入字体轮廓时对应的字体 ID
+    pub fn outline_font_id(&self) -> Option<CharacterId> {
+        self.outline_font().map(|_| self.font_id)
+    }
+
+    /// 关联加载时细分得到的字形网格
+    pub(crate) fn set_glyph_shapes(&mut self, shapes: Arc<GlyphShapes>) {
+        self.glyph_shapes = shapes;
+        self.relayout();
+    }
+
+    pub fn bounds(&self) -> &Rectangle<Twips> {
+        &self.bounds
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 设置文本，HTML 文本框中可以使用 `<br>`、`<p>` 与 `<font color="#rrggbb">`
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.relayout();
+    }
+
+    fn has_flag(&self, flag: EditTextFlag) -> bool {
+        self.flags.contains(flag)
+    }
+
+    /// 使用嵌入字体轮廓绘制时的字体
+    fn outline_font(&self) -> Option<&Arc<Font>> {
+        self.font
+            .as_ref()
+            .filter(|_| self.has_flag(EditTextFlag::USE_OUTLINES))
+    }
+
+    fn styled_chars(&self) -> Vec<StyledChar> {
+        if self.has_flag(EditTextFlag::HTML) {
+            parse_html(&self.text, self.color)
+        } else {
+            self.text.chars().map(|c| (c, self.color)).collect()
+        }
+    }
+
+    /// 去掉格式后的文本，用于设备字体
+    fn plain_text(&self) -> String {
+        self.styled_chars().into_iter().map(|(c, _)| c).collect()
+    }
+
+    /// 可用于排版的宽度
+    fn content_width(&self) -> Twips {
+        self.field_bounds.x_max
+            - self.field_bounds.x_min
+            - GUTTER * 2
+            - self.left_margin
+            - self.right_margin
+    }
+
+    /// 使用嵌入字体重新排版
+    fn relayout(&mut self) {
+        self.glyphs.clear();
+        self.bounds = self.field_bounds.clone();
+        let Some(font) = self.outline_font().cloned() else {
+            return;
+        };
+        let scale = self.height.get() as f32 / font.scale();
+        let em = |value: f32| Twips::new((value * scale).round() as i32);
+        let word_wrap =
+            self.has_flag(EditTextFlag::WORD_WRAP) && self.has_flag(EditTextFlag::MULTILINE);
+        let width = self.content_width();
+
+        // 断行
+        let mut lines: Vec<Vec<LineGlyph>> = vec![Vec::new()];
+        let mut chars = self.styled_chars().into_iter().peekable();
+        while let Some((c, color)) = chars.next() {
+            if c == '\r' || c == '\n' {
+                if c == '\r' && chars.peek().is_some_and(|(next, _)| *next == '\n') {
+                    chars.next();
+                }
+                lines.push(Vec::new());
+                continue;
+            }
+            let index = font.glyph_index(c);
+            let is_space = c.is_whitespace();
+            let advance = match index {
+                Some(index) => em(font.advance(index)),
+                None if is_space => em(font.scale() / 4.0),
+                None => continue,
+            };
+            let indent = if lines.len() == 1 {
+                self.indent
+            } else {
+                Twips::ZERO
+            };
+            let line = lines.last_mut().unwrap();
+            let current_width = line_width(line) + indent;
+            if word_wrap && !is_space && !line.is_empty() && current_width + advance > width {
+                // 在最后一个空格处换行，单词比整行更长时直接截断
+                let split = line
+                    .iter()
+                    .rposition(|g| g.is_space)
+                    .map_or(line.len(), |i| i + 1);
+                let rest = line.split_off(split);
+                lines.push(rest);
+            }
+            lines.last_mut().unwrap().push(LineGlyph {
+                index,
+                color,
+                advance,
+                is_space,
+            });
+        }
+
+        let ascent = em(font.ascent());
+        let line_height = ascent + em(font.descent()) + self.leading;
+        let line_widths = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                // 行尾的空格不参与对齐
+                let trimmed = line.iter().rposition(|g| !g.is_space).map_or(0, |i| i + 1);
+                let indent = if i == 0 { self.indent } else { Twips::ZERO };
+                line_width(&line[..trimmed]) + indent
+            })
+            .collect::<Vec<_>>();
+
+        // 自动大小，宽度只在不自动换行时调整
+        if self.has_flag(EditTextFlag::AUTO_SIZE) {
+            if !word_wrap {
+                let max_width = line_widths.iter().copied().max().unwrap_or_default();
+                let extra = max_width - width;
+                match self.align {
+                    TextAlign::Left | TextAlign::Justify => self.bounds.x_max += extra,
+                    TextAlign::Right => self.bounds.x_min -= extra,
+                    TextAlign::Center => {
+                        self.bounds.x_min -= extra / 2;
+                        self.bounds.x_max = self.bounds.x_min
+                            + (self.field_bounds.x_max - self.field_bounds.x_min)
+                            + extra;
+                    }
+                }
+            }
+            self.bounds.y_max =
+                self.bounds.y_min + GUTTER * 2 + line_height * lines.len() as i32 - self.leading;
+        }
+
+        let content_width = self.bounds.x_max
+            - self.bounds.x_min
+            - GUTTER * 2
+            - self.left_margin
+            - self.right_margin;
+        let mut y = self.bounds.y_min + GUTTER + ascent;
+        for (i, line) in lines.iter().enumerate() {
+            let free = content_width - line_widths[i];
+            let offset = match self.align {
+                TextAlign::Left | TextAlign::Justify => Twips::ZERO,
+                TextAlign::Center => free / 2,
+                TextAlign::Right => free,
+            };
+            let indent = if i == 0 { self.indent } else { Twips::ZERO };
+            let mut x = self.bounds.x_min + GUTTER + self.left_margin + indent + offset;
+            for glyph in line {
+                if let Some(index) = glyph.index {
+                    self.glyphs.push(TextGlyph {
+                        font_id: self.font_id,
+                        index,
+                        transform: Transform {
+                            matrix: Matrix::create_box(scale, scale, x, y),
+                            color_transform: ColorTransform::multiply_from(glyph.color),
+                        },
+                        shape: self.glyph_shapes.get(&(self.font_id, index)).cloned(),
+                    });
+                }
+                x += glyph.advance;
+            }
+            y += line_height;
+        }
+    }
+}
+
+fn line_width(line: &[LineGlyph]) -> Twips {
+    line.iter()
+        .fold(Twips::ZERO, |width, glyph| width + glyph.advance)
+}
+
+/// 解析简单的 HTML 文本，支持 `<br>`、`<p>`、`<font color>` 与常用的字符实体，其它标签会被忽略
+fn parse_html(html: &str, color: Color) -> Vec<StyledChar> {
+    let mut result = Vec::new();
+    let mut colors = vec![color];
+    let mut chars = html.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => {
+                let tag = chars.by_ref().take_while(|c| *c != '>').collect::<String>();
+                let tag = tag.trim().to_ascii_lowercase();
+                let name = tag.split_whitespace().next().unwrap_or_default();
+                match name.trim_end_matches('/') {
+                    "br" | "/p" => result.push(('\n', *colors.last().unwrap())),
+                    "font" => {
+                        let color = font_color(&tag).unwrap_or(*colors.last().unwrap());
+                        colors.push(color);
+                    }
+                    "/font" if colors.len() > 1 => {
+                        colors.pop();
+                    }
+                    _ => {}
+                }
+            }
+            '&' => {
+                let rest = chars.as_str();
+                let entity = rest
+                    .find(';')
+                    .filter(|end| *end <= 8)
+                    .and_then(|end| html_entity(&rest[..end]).map(|c| (c, end)));
+                match entity {
+                    Some((c, end)) => {
+                        result.push((c, *colors.last().unwrap()));
+                        chars = rest[end + 1..].chars();
+                    }
+                    None => result.push(('&', *colors.last().unwrap())),
+                }
+            }
+            c => result.push((c, *colors.last().unwrap())),
+        }
+    }
+    // 最后一个段落结束时不需要换行
+    if result.last().is_some_and(|(c, _)| *c == '\n') {
+        result.pop();
+    }
+    result
+}
+
+/// `<font color="#rrggbb">` 中的颜色
+fn font_color(tag: &str) -> Option<Color> {
+    let value = tag.split("color=").nth(1)?;
+    let value = value.trim_start_matches(['"', '\'']);
+    let hex = value.strip_prefix('#')?.get(..6)?;
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color::from_rgb(rgb, 255))
+}
+
+fn html_entity(name: &str) -> Option<char> {
+    Some(match name {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        _ => return None,
+    })
+}
+
+/// 将颜色变换应用到设备字体的文本颜色上
+fn transform_color(color: Color, color_transform: &ColorTransform) -> Color {
+    let mult = color_transform.mult_rgba_normalized();
+    let add = color_transform.add_rgba_normalized();
+    let channel = |value: u8, index: usize| {
+        ((f32::from(value) / 255.0 * mult[index] + add[index]).clamp(0.0, 1.0) * 255.0).round()
+            as u8
+    };
+    Color {
+        r: channel(color.r, 0),
+        g: channel(color.g, 1),
+        b: channel(color.b, 2),
+        a: channel(color.a, 3),
+    }
+}
+
+impl TDisplayObject for EditText {
+    fn base(&self) -> &DisplayObjectBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut DisplayObjectBase {
+        &mut self.base
+    }
+
+    fn movie(&self) -> Arc<SwfMovie> {
+        self.movie.clone()
+    }
+
+    fn replace_with(&mut self, id: CharacterId, characters: &HashMap<CharacterId, Character>) {
+        if let Some(Character::EditText(edit_text)) = characters.get(&id) {
+            let text = std::mem::take(&mut self.text);
+            *self = Self {
+                base: std::mem::take(&mut self.base),
+                ..edit_text.clone()
+            };
+            // 替换字符时保留运行时设置的文本
+            self.set_text(text);
+        }
+    }
+
+    fn self_bounds(&mut self, _context: &mut RenderContext) -> Rectangle<Twips> {
+        self.bounds.clone()
+    }
+
+    fn id(&self) -> CharacterId {
+        self.id
+    }
+
+    fn render_self(&mut self, context: &mut RenderContext, blend_mode: BlendMode) {
+        if self.outline_font().is_some() {
+            render_glyphs(&self.glyphs, context, blend_mode);
+            return;
+        }
+        let word_wrap =
+            self.has_flag(EditTextFlag::WORD_WRAP) && self.has_flag(EditTextFlag::MULTILINE);
+        let transform = context.transform_stack.transform();
+        let matrix = transform.matrix
+            * Matrix::translate(
+                self.bounds.x_min + GUTTER + self.left_margin,
+                self.bounds.y_min + GUTTER,
+            );
+        context.device_texts.push(DeviceText {
+            text: self.plain_text(),
+            matrix,
+            font_size: self.height.to_pixels() as f32,
+            color: transform_color(self.color, &transform.color_transform),
+            width: word_wrap.then(|| self.content_width().to_pixels() as f32),
+            align: self.align,
+        });
+    }
+}
+
+impl From<EditText> for DisplayObject {
+    fn from(edit_text: EditText) -> Self {
+        Self::EditText(edit_text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swf::{Color, ColorTransform, Fixed8};
+
+    use super::{parse_html, transform_color};
+
+    fn text(chars: &[(char, Color)]) -> String {
+        chars.iter().map(|(c, _)| c).collect()
+    }
+
+    #[test]
+    fn html_line_breaks_and_entities() {
+        let chars = parse_html("<p>HP &lt;120&gt;</p><p>MP<br/>80</p>", Color::BLACK);
+        assert_eq!(text(&chars), "HP <120>\nMP\n80");
+    }
+
+    #[test]
+    fn html_font_color_spans() {
+        let red = Color::from_rgb(0xff0000, 255);
+        let chars = parse_html("a<font color=\"#FF0000\">b</font>c", Color::BLACK);
+        assert_eq!(text(&chars), "abc");
+        assert_eq!(chars[0].1, Color::BLACK);
+        assert_eq!(chars[1].1, red);
+        assert_eq!(chars[2].1, Color::BLACK);
+    }
+
+    #[test]
+    fn unknown_entity_is_kept() {
+        let chars = parse_html("a & b &unknown;", Color::BLACK);
+        assert_eq!(text(&chars), "a & b &unknown;");
+    }
+
+    #[test]
+    fn device_text_color_follows_color_transform() {
+        let color_transform = ColorTransform {
+            r_multiply: Fixed8::from_f32(0.5),
+            a_multiply: Fixed8::from_f32(0.5),
+            g_add: 255,
+            ..ColorTransform::IDENTITY
+        };
+        let color = transform_color(Color::from_rgb(0xff0000, 255), &color_transform);
+        assert_eq!(
+            color,
+            Color {
+                r: 128,
+                g: 255,
+                b: 0,
+                a: 128,
+            }
+        );
+    }
+}
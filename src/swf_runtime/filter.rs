@@ -1,3 +1,4 @@
+use bevy::{asset::Handle, image::Image};
 use swf::{Rectangle, Twips};
 
 /// 用于渲染的滤镜结构
@@ -11,6 +12,42 @@ pub enum Filter {
     GlowFilter(swf::GlowFilter),
     GradientBevelFilter(swf::GradientFilter),
     GradientGlowFilter(swf::GradientFilter),
+    /// 置换图滤镜在 Flash 中只能通过 ActionScript 创建，SWF 标签中不会出现，
+    /// 需要通过 `MovieClip::set_displacement_map` 附加
+    DisplacementMapFilter(DisplacementMapFilter),
+}
+
+/// 置换图滤镜超出范围时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplacementMapFilterMode {
+    /// 环绕到另一侧
+    Wrap,
+    /// 限制在边缘
+    Clamp,
+    /// 不进行置换
+    Ignore,
+    /// 使用指定颜色
+    Color,
+}
+
+/// 置换图滤镜
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplacementMapFilter {
+    /// 置换图
+    pub map_bitmap: Handle<Image>,
+    /// 置换图左上角相对于显示对象的偏移（像素）
+    pub map_point: (i32, i32),
+    /// 用于 x 方向置换的颜色通道，1 红 2 绿 4 蓝 8 透明度
+    pub component_x: u8,
+    /// 用于 y 方向置换的颜色通道，1 红 2 绿 4 蓝 8 透明度
+    pub component_y: u8,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub mode: DisplacementMapFilterMode,
+    /// `mode` 为 [`DisplacementMapFilterMode::Color`] 时超出范围使用的颜色
+    pub color: swf::Color,
+    pub viewscale_x: f32,
+    pub viewscale_y: f32,
 }
 
 impl Filter {
@@ -22,6 +59,10 @@ impl Filter {
             Filter::GlowFilter(filter) => filter.scale(x, y),
             Filter::GradientBevelFilter(filter) => filter.scale(x, y),
             Filter::GradientGlowFilter(filter) => filter.scale(x, y),
+            Filter::DisplacementMapFilter(filter) => {
+                filter.viewscale_x = x;
+                filter.viewscale_y = y;
+            }
             _ => {}
         }
     }
@@ -32,7 +73,13 @@ impl Filter {
             Filter::GlowFilter(filter) => filter.calculate_dest_rect(source_rect),
             Filter::DropShadowFilter(filter) => filter.calculate_dest_rect(source_rect),
             Filter::BevelFilter(filter) => filter.calculate_dest_rect(source_rect),
-            _ => source_rect,
+            Filter::GradientBevelFilter(filter) | Filter::GradientGlowFilter(filter) => {
+                gradient_filter_dest_rect(filter, source_rect)
+            }
+            // 卷积与置换图滤镜不会改变显示对象的大小
+            Filter::ColorMatrixFilter(_)
+            | Filter::ConvolutionFilter(_)
+            | Filter::DisplacementMapFilter(_) => source_rect,
         }
     }
 
@@ -48,6 +95,33 @@ impl Filter {
     }
 }
 
+/// 渐变滤镜模糊使用的内部模糊滤镜
+pub fn gradient_filter_blur(filter: &swf::GradientFilter) -> swf::BlurFilter {
+    swf::BlurFilter {
+        blur_x: filter.blur_x,
+        blur_y: filter.blur_y,
+        flags: swf::BlurFilterFlags::from_passes(filter.num_passes()),
+    }
+}
+
+/// 渐变发光和渐变斜角滤镜的目标矩形：模糊扩展后再向两侧扩展偏移距离
+fn gradient_filter_dest_rect(
+    filter: &swf::GradientFilter,
+    source_rect: Rectangle<Twips>,
+) -> Rectangle<Twips> {
+    let blur_rect = gradient_filter_blur(filter).calculate_dest_rect(source_rect);
+    let distance = filter.distance.to_f64();
+    let angle = filter.angle.to_f64();
+    let x = Twips::from_pixels((angle.cos() * distance).abs().ceil());
+    let y = Twips::from_pixels((angle.sin() * distance).abs().ceil());
+    Rectangle {
+        x_min: blur_rect.x_min - x,
+        x_max: blur_rect.x_max + x,
+        y_min: blur_rect.y_min - y,
+        y_max: blur_rect.y_max + y,
+    }
+}
+
 impl From<&swf::Filter> for Filter {
     fn from(value: &swf::Filter) -> Self {
         match value {
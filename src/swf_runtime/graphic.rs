@@ -1,15 +1,132 @@
 use std::sync::Arc;
 
-use bevy::platform::collections::HashMap;
+use bevy::asset::Handle;
+use bevy::math::{IVec2, Vec2};
+use bevy::platform::collections::{HashMap, HashSet};
 use swf::{BlendMode, CharacterId, Rectangle, Twips};
 
 use crate::RenderContext;
+use crate::assets::Shape;
 use crate::swf_runtime::character::Character;
 
 use super::tag_utils::SwfMovie;
+use super::tessellator::{
+    ShapeTessellator, StrokeTransform, has_pixel_hinted_strokes, has_scale_dependent_strokes,
+};
 
 use super::display_object::{DisplayObject, DisplayObjectBase, TDisplayObject};
 
+/// 屏幕缩放的量化精度，避免细微的缩放变化导致重复细分
+const STROKE_SCALE_STEPS: f32 = 32.0;
+
+/// 像素对齐时屏幕平移小数部分的量化精度
+const SUBPIXEL_STEPS: f32 = 8.0;
+
+/// 按屏幕变换重新细分的形状的缓存 key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct StrokeShapeKey {
+    id: CharacterId,
+    /// 变形形状的比例，图形为 0
+    ratio: u16,
+    /// 按 [`STROKE_SCALE_STEPS`] 量化的屏幕缩放
+    scale: IVec2,
+    /// 按 [`SUBPIXEL_STEPS`] 量化的屏幕平移小数部分，只有含像素对齐线条的形状使用
+    subpixel: IVec2,
+}
+
+impl StrokeShapeKey {
+    /// 根据当前的变换计算 key，平移的整数部分不影响像素对齐的结果，因此只保留小数部分
+    pub(crate) fn new(
+        id: CharacterId,
+        ratio: u16,
+        pixel_hinted: bool,
+        context: &RenderContext,
+    ) -> Self {
+        let matrix = context.transform_stack.transform().matrix;
+        let view_scale = Vec2::new(context.scale.x.abs(), context.scale.y.abs());
+        let scale = Vec2::new(
+            (matrix.a * matrix.a + matrix.b * matrix.b).sqrt(),
+            (matrix.c * matrix.c + matrix.d * matrix.d).sqrt(),
+        ) * view_scale;
+        let subpixel = if pixel_hinted {
+            let translation =
+                Vec2::new(matrix.tx.to_pixels() as f32, matrix.ty.to_pixels() as f32) * view_scale;
+            ((translation - translation.floor()) * SUBPIXEL_STEPS)
+                .round()
+                .as_ivec2()
+                % SUBPIXEL_STEPS as i32
+        } else {
+            IVec2::ZERO
+        };
+        Self {
+            id,
+            ratio,
+            scale: (scale * STROKE_SCALE_STEPS)
+                .round()
+                .as_ivec2()
+                .max(IVec2::ONE),
+            subpixel,
+        }
+    }
+
+    /// 量化后的屏幕变换，用于细分
+    fn stroke_transform(&self) -> StrokeTransform {
+        StrokeTransform {
+            scale: self.scale.as_vec2() / STROKE_SCALE_STEPS,
+            translation: self.subpixel.as_vec2() / SUBPIXEL_STEPS,
+        }
+    }
+}
+
+/// 按屏幕变换重新细分的形状缓存。
+///
+/// 缩放补间几乎每帧都会产生新的 key，因此只保留最近一次绘制中使用过的形状，
+/// 其余形状在绘制结束后由 [`StrokeShapeCache::evict_unused`] 释放。
+#[derive(Default)]
+pub(crate) struct StrokeShapeCache {
+    shapes: HashMap<StrokeShapeKey, Handle<Shape>>,
+    used: HashSet<StrokeShapeKey>,
+}
+
+impl StrokeShapeCache {
+    pub(crate) fn get(&mut self, key: StrokeShapeKey) -> Option<Handle<Shape>> {
+        let handle = self.shapes.get(&key).cloned();
+        if handle.is_some() {
+            self.used.insert(key);
+        }
+        handle
+    }
+
+    fn insert(&mut self, key: StrokeShapeKey, handle: Handle<Shape>) {
+        self.used.insert(key);
+        self.shapes.insert(key, handle);
+    }
+
+    /// 释放本次绘制中没有使用的形状，每次绘制结束后调用
+    pub(crate) fn evict_unused(&mut self) {
+        let used = &self.used;
+        self.shapes.retain(|key, _| used.contains(key));
+        self.used.clear();
+    }
+}
+
+/// 按 `key` 中的屏幕变换细分形状并加入缓存
+pub(crate) fn tessellate_stroke_shape(
+    context: &mut RenderContext,
+    key: StrokeShapeKey,
+    shape: &swf::Shape,
+) -> Handle<Shape> {
+    let mut tessellator = ShapeTessellator::new(context.mesh_settings.tolerance);
+    let lyon_mesh = tessellator.tessellate_shape(
+        shape.into(),
+        context.bitmap_textures,
+        key.stroke_transform(),
+    );
+    let handle = context.add_tessellated_shape(lyon_mesh);
+    context.stroke_shape_cache.insert(key, handle.clone());
+    handle
+}
+
 #[derive(Debug, Clone)]
 pub struct Graphic {
     id: CharacterId,
@@ -17,6 +134,10 @@ pub struct Graphic {
     shape: swf::Shape,
     bounds: Rectangle<Twips>,
     movie: Arc<SwfMovie>,
+    /// 是否含有不缩放线条、细线或像素对齐的线条，这类图形需要按屏幕缩放重新细分
+    scale_dependent_strokes: bool,
+    /// 是否含有像素对齐的线条
+    pixel_hinted_strokes: bool,
 }
 
 impl Graphic {
@@ -25,15 +146,34 @@ impl Graphic {
             id: shape.id,
             base: Default::default(),
             bounds: shape.shape_bounds.clone(),
+            scale_dependent_strokes: has_scale_dependent_strokes(&shape),
+            pixel_hinted_strokes: has_pixel_hinted_strokes(&shape),
             shape,
             movie,
         }
     }
 
+    pub fn has_scale_dependent_strokes(&self) -> bool {
+        self.scale_dependent_strokes
+    }
+
+    /// 按当前的屏幕变换细分图形，结果按量化后的变换缓存
+    fn scale_dependent_shape(&self, context: &mut RenderContext) -> Handle<Shape> {
+        let key = StrokeShapeKey::new(self.id, 0, self.pixel_hinted_strokes, context);
+        if let Some(handle) = context.stroke_shape_cache.get(key) {
+            return handle;
+        }
+        tessellate_stroke_shape(context, key, &self.shape)
+    }
+
     pub fn id(&self) -> CharacterId {
         self.id
     }
 
+    pub fn bounds(&self) -> &Rectangle<Twips> {
+        &self.bounds
+    }
+
     pub fn shape(&self) -> &swf::Shape {
         &self.shape
     }
@@ -66,6 +206,8 @@ impl TDisplayObject for Graphic {
             self.shape = graphic.shape.clone();
             self.bounds = graphic.bounds.clone();
             self.movie = graphic.movie.clone();
+            self.scale_dependent_strokes = graphic.scale_dependent_strokes;
+            self.pixel_hinted_strokes = graphic.pixel_hinted_strokes;
         }
     }
 
@@ -78,7 +220,11 @@ impl TDisplayObject for Graphic {
     }
 
     fn render_self(&mut self, context: &mut RenderContext, blend_mode: BlendMode) {
-        let handle = context.shape_handles.get(&self.id).unwrap().clone();
+        let handle = if self.scale_dependent_strokes {
+            self.scale_dependent_shape(context)
+        } else {
+            context.shape_handles.get(&self.id).unwrap().clone()
+        };
         context.render_shape(
             handle,
             context.transform_stack.transform(),
@@ -1,12 +1,16 @@
+pub(crate) mod button;
 pub(crate) mod character;
 pub(crate) mod decoder;
 pub(crate) mod display_object;
+pub(crate) mod edit_text;
 pub(crate) mod filter;
 pub(crate) mod graphic;
 pub(crate) mod matrix;
 pub(crate) mod morph_shape;
 pub mod movie_clip;
 pub(crate) mod shape_utils;
+pub(crate) mod sound;
 pub(crate) mod tag_utils;
 pub(crate) mod tessellator;
+pub(crate) mod text;
 pub(crate) mod transform;
@@ -1,26 +1,15 @@
-use bevy::{
-    asset::{Handle, RenderAssetUsages},
-    color::ColorToComponents,
-    log::warn,
-    math::{Mat3, Mat4},
-    mesh::{Indices, Mesh, PrimitiveTopology},
-    platform::collections::HashMap,
-};
-use copyless::VecHelper;
+use bevy::{asset::Handle, log::warn, platform::collections::HashMap};
 use std::sync::Arc;
 use swf::{CharacterId, Color, Fixed8, Fixed16, Point, Rectangle, Twips};
 
-use crate::{
-    assets::MeshDraw,
-    swf_runtime::{
-        shape_utils::calculate_shape_bounds,
-        tessellator::{DrawType, ShapeTessellator},
+use crate::assets::Shape;
+use crate::swf_runtime::{
+    graphic::{StrokeShapeKey, tessellate_stroke_shape},
+    shape_utils::calculate_shape_bounds,
+    tessellator::{
+        ShapeTessellator, StrokeTransform, has_pixel_hinted_strokes, has_scale_dependent_strokes,
     },
 };
-use crate::{
-    assets::{MaterialType, Shape, create_gradient_textures},
-    render::material::GradientMaterial,
-};
 
 use super::{
     display_object::{DisplayObject, DisplayObjectBase, TDisplayObject},
@@ -34,6 +23,10 @@ pub struct Frame {
     handle: Option<Handle<Shape>>,
     shape: swf::Shape,
     bounds: Rectangle<Twips>,
+    /// 是否含有不缩放线条、细线或像素对齐的线条，这类帧需要按屏幕变换重新细分
+    scale_dependent_strokes: bool,
+    /// 是否含有像素对齐的线条
+    pixel_hinted_strokes: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -75,79 +68,30 @@ impl MorphShape {
 
     fn get_shape(&mut self, ratio: u16, context: &mut crate::RenderContext) -> Handle<Shape> {
         let frame = self.get_frame(ratio, context.morph_shape_cache);
+        if frame.scale_dependent_strokes {
+            let pixel_hinted = frame.pixel_hinted_strokes;
+            let key = StrokeShapeKey::new(self.id(), ratio, pixel_hinted, context);
+            if let Some(handle) = context.stroke_shape_cache.get(key) {
+                return handle;
+            }
+            let shape = self
+                .get_frame(ratio, context.morph_shape_cache)
+                .shape
+                .clone();
+            return tessellate_stroke_shape(context, key, &shape);
+        }
         if let Some(handle) = &frame.handle {
             handle.clone()
         } else {
-            let bitmaps = HashMap::new();
-            let mut tessellator = ShapeTessellator::default();
+            let mut tessellator = ShapeTessellator::new(context.mesh_settings.tolerance);
             let shape = &frame.shape;
-            let lyon_mesh = tessellator.tessellate_shape(shape.into(), &bitmaps);
-            let mut gradient_texture = Vec::new();
-            for (texture, gradient_uniforms) in create_gradient_textures(lyon_mesh.gradients) {
-                gradient_texture.push((context.images.add(texture), gradient_uniforms));
-            }
-            let mut shape = Vec::new();
-            for draw in lyon_mesh.draws {
-                match &draw.draw_type {
-                    DrawType::Color => {
-                        let mut positions = Vec::with_capacity(draw.vertices.len());
-                        let mut colors = Vec::with_capacity(draw.vertices.len());
-                        for vertex in &draw.vertices {
-                            positions.alloc().init([vertex.x, vertex.y, 0.0]);
-                            let linear_color = bevy::color::Color::srgba_u8(
-                                vertex.color.r,
-                                vertex.color.g,
-                                vertex.color.b,
-                                vertex.color.a,
-                            )
-                            .to_linear();
-                            colors.alloc().init(linear_color.to_f32_array());
-                        }
-                        let mesh = Mesh::new(
-                            PrimitiveTopology::TriangleList,
-                            RenderAssetUsages::RENDER_WORLD,
-                        )
-                        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
-                        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
-                        .with_inserted_indices(Indices::U32(draw.indices.into_iter().collect()));
-                        let mesh = context.meshes.add(mesh);
-                        shape.push(MeshDraw {
-                            mesh,
-                            material_type: MaterialType::Color(context.color_material.clone()),
-                        });
-                    }
-                    DrawType::Gradient { matrix, gradient } => {
-                        let Some((handle, gradient)) = gradient_texture.get(*gradient).cloned()
-                        else {
-                            continue;
-                        };
-                        let mut positions = Vec::with_capacity(draw.vertices.len());
-                        for vertex in &draw.vertices {
-                            positions.alloc().init([vertex.x, vertex.y, 0.0]);
-                        }
-                        let mesh = Mesh::new(
-                            PrimitiveTopology::TriangleList,
-                            RenderAssetUsages::default(),
-                        )
-                        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
-                        .with_inserted_indices(Indices::U32(draw.indices.into_iter().collect()));
-                        let mesh = context.meshes.add(mesh);
-                        let material = context.gradients.add(GradientMaterial {
-                            gradient,
-                            texture: handle,
-                            texture_transform: Mat4::from_mat3(Mat3::from_cols_array_2d(matrix)),
-                            ..Default::default()
-                        });
-                        shape.push(MeshDraw {
-                            mesh,
-                            material_type: MaterialType::Gradient(material),
-                        });
-                    }
-                    _ => {}
-                }
-            }
-            let handle = context.shapes.add(Shape(shape));
-            frame.handle = Some(handle.clone());
+            let lyon_mesh = tessellator.tessellate_shape(
+                shape.into(),
+                context.bitmap_textures,
+                StrokeTransform::IDENTITY,
+            );
+            let handle = context.add_tessellated_shape(lyon_mesh);
+            self.get_frame(ratio, context.morph_shape_cache).handle = Some(handle.clone());
             handle
         }
     }
@@ -274,6 +218,8 @@ impl MorphShape {
 
         Frame {
             handle: None,
+            scale_dependent_strokes: has_scale_dependent_strokes(&shape),
+            pixel_hinted_strokes: has_pixel_hinted_strokes(&shape),
             shape,
             bounds,
         }
@@ -305,6 +251,11 @@ impl MorphShape {
     pub fn set_ratio(&mut self, ratio: u16) {
         self.ratio = ratio;
     }
+
+    /// 指定变形比例下的形状
+    pub fn shape(&self, ratio: u16) -> swf::Shape {
+        Self::build_morph_frame(&self.start, &self.end, ratio).shape
+    }
 }
 
 impl TDisplayObject for MorphShape {
@@ -536,21 +487,63 @@ fn lerp_edges(
     }
 }
 
+/// 插值填充矩阵。
+///
+/// 逐元素插值会使旋转中的填充在中途发生切变，这里将矩阵分解为 X/Y 轴的缩放与旋转角
+/// （两轴旋转角之差即为切变），分别插值后再重新组合。旋转角沿最短方向插值。
 fn lerp_matrix(start: &swf::Matrix, end: &swf::Matrix, a: f32, b: f32) -> swf::Matrix {
-    // TODO: Lerping a matrix element-wise is geometrically wrong,
-    // but I doubt Flash is decomposing the matrix into scale-rotate-translate?
-    let af = Fixed16::from_f32(a);
-    let bf = Fixed16::from_f32(b);
+    let start_parts = MatrixParts::from(start);
+    let end_parts = MatrixParts::from(end);
+    let parts = MatrixParts {
+        scale_x: start_parts.scale_x * a + end_parts.scale_x * b,
+        scale_y: start_parts.scale_y * a + end_parts.scale_y * b,
+        rotation_x: lerp_angle(start_parts.rotation_x, end_parts.rotation_x, b),
+        rotation_y: lerp_angle(start_parts.rotation_y, end_parts.rotation_y, b),
+    };
+    let (sin_x, cos_x) = parts.rotation_x.sin_cos();
+    let (sin_y, cos_y) = parts.rotation_y.sin_cos();
     swf::Matrix {
-        a: start.a * af + end.a * bf,
-        b: start.b * af + end.b * bf,
-        c: start.c * af + end.c * bf,
-        d: start.d * af + end.d * bf,
+        a: Fixed16::from_f32(parts.scale_x * cos_x),
+        b: Fixed16::from_f32(parts.scale_x * sin_x),
+        c: Fixed16::from_f32(-parts.scale_y * sin_y),
+        d: Fixed16::from_f32(parts.scale_y * cos_y),
         tx: lerp_twips(start.tx, end.tx, a, b),
         ty: lerp_twips(start.ty, end.ty, a, b),
     }
 }
 
+/// 分解后的矩阵，与 Flash 中 `DisplayObject` 的缩放、旋转分解方式一致
+struct MatrixParts {
+    scale_x: f32,
+    scale_y: f32,
+    rotation_x: f32,
+    rotation_y: f32,
+}
+
+impl From<&swf::Matrix> for MatrixParts {
+    fn from(matrix: &swf::Matrix) -> Self {
+        let (a, b, c, d) = (
+            matrix.a.to_f32(),
+            matrix.b.to_f32(),
+            matrix.c.to_f32(),
+            matrix.d.to_f32(),
+        );
+        Self {
+            scale_x: (a * a + b * b).sqrt(),
+            scale_y: (c * c + d * d).sqrt(),
+            rotation_x: b.atan2(a),
+            rotation_y: (-c).atan2(d),
+        }
+    }
+}
+
+/// 沿最短方向插值角度（弧度）
+fn lerp_angle(start: f32, end: f32, factor: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    let delta = (end - start + PI).rem_euclid(TAU) - PI;
+    start + delta * factor
+}
+
 fn lerp_gradient(start: &swf::Gradient, end: &swf::Gradient, a: f32, b: f32) -> swf::Gradient {
     use swf::{Gradient, GradientRecord};
     // Morph gradients are guaranteed to have the same number of records in the start/end gradient.
@@ -572,3 +565,90 @@ fn lerp_gradient(start: &swf::Gradient, end: &swf::Gradient, a: f32, b: f32) ->
         records,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use swf::{Fixed16, Twips};
+
+    use super::lerp_matrix;
+
+    fn matrix(a: f32, b: f32, c: f32, d: f32, tx: i32, ty: i32) -> swf::Matrix {
+        swf::Matrix {
+            a: Fixed16::from_f32(a),
+            b: Fixed16::from_f32(b),
+            c: Fixed16::from_f32(c),
+            d: Fixed16::from_f32(d),
+            tx: Twips::new(tx),
+            ty: Twips::new(ty),
+        }
+    }
+
+    fn rotation(angle: f32, scale: f32) -> swf::Matrix {
+        let (sin, cos) = angle.sin_cos();
+        matrix(scale * cos, scale * sin, -scale * sin, scale * cos, 0, 0)
+    }
+
+    fn lerp(start: &swf::Matrix, end: &swf::Matrix, ratio: f32) -> swf::Matrix {
+        lerp_matrix(start, end, 1.0 - ratio, ratio)
+    }
+
+    #[track_caller]
+    fn assert_matrix_eq(actual: swf::Matrix, expected: swf::Matrix) {
+        let close = |x: Fixed16, y: Fixed16| (x.to_f32() - y.to_f32()).abs() < 1e-3;
+        assert!(
+            close(actual.a, expected.a)
+                && close(actual.b, expected.b)
+                && close(actual.c, expected.c)
+                && close(actual.d, expected.d)
+                && actual.tx == expected.tx
+                && actual.ty == expected.ty,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn endpoints_are_preserved() {
+        let start = matrix(1.5, 0.25, -0.5, 0.75, 100, -40);
+        let end = matrix(-0.5, 1.0, -2.0, 0.25, -60, 300);
+        assert_matrix_eq(lerp(&start, &end, 0.0), start);
+        assert_matrix_eq(lerp(&start, &end, 1.0), end);
+    }
+
+    #[test]
+    fn rotation_keeps_scale() {
+        let start = rotation(0.0, 1.0);
+        let end = rotation(FRAC_PI_2, 1.0);
+        for ratio in [0.25, 0.5, 0.75] {
+            assert_matrix_eq(lerp(&start, &end, ratio), rotation(FRAC_PI_2 * ratio, 1.0));
+        }
+    }
+
+    #[test]
+    fn rotation_and_scale_are_interpolated_separately() {
+        let start = rotation(0.0, 1.0);
+        let end = rotation(FRAC_PI_2, 3.0);
+        assert_matrix_eq(lerp(&start, &end, 0.5), rotation(FRAC_PI_2 / 2.0, 2.0));
+        assert_matrix_eq(lerp(&start, &end, 0.25), rotation(FRAC_PI_2 / 4.0, 1.5));
+    }
+
+    #[test]
+    fn rotation_takes_shortest_path() {
+        let start = rotation(170f32.to_radians(), 1.0);
+        let end = rotation((-170f32).to_radians(), 1.0);
+        assert_matrix_eq(lerp(&start, &end, 0.5), rotation(180f32.to_radians(), 1.0));
+    }
+
+    #[test]
+    fn skew_and_translation_are_interpolated() {
+        let start = matrix(1.0, 0.0, 0.0, 1.0, 0, 0);
+        // X 轴不变，Y 轴旋转 90°，即水平切变
+        let end = matrix(1.0, 0.0, -2.0, 0.0, 200, -100);
+        let (sin, cos) = (FRAC_PI_2 / 2.0).sin_cos();
+        assert_matrix_eq(
+            lerp(&start, &end, 0.5),
+            matrix(1.0, 0.0, -1.5 * sin, 1.5 * cos, 100, -50),
+        );
+    }
+}
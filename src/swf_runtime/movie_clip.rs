@@ -10,20 +10,26 @@ use smallvec::SmallVec;
 use swf::extensions::ReadSwfExt;
 use swf::read::Reader;
 use swf::{
-    CharacterId, Color, DefineBitsLossless, Depth, PlaceObjectAction, Rectangle, TagCode, Twips,
+    CharacterId, Color, DefineBitsLossless, Depth, PlaceObjectAction, Point, Rectangle, TagCode,
+    Twips,
 };
 
-use crate::assets::MovieLibrary;
+use crate::assets::{FrameEvent, MovieLibrary, frame_events_from_labels};
 
+use super::button::{Button, PointerContext};
 use super::character::{BitmapLibrary, Character, CompressedBitmap, instantiate_by_id};
 use super::decoder::{
     decode_define_bits_jpeg_dimensions, glue_tables_to_jpeg, remove_invalid_jpeg_data,
 };
 use super::display_object::{DisplayObject, DisplayObjectBase, FrameNumber, TDisplayObject};
+use super::edit_text::EditText;
+use super::filter::DisplacementMapFilter;
 use super::graphic::Graphic;
 use super::morph_shape::MorphShape;
+use super::sound::{SoundData, SoundStream, SoundTarget, StartSound, decode_define_sound};
 use super::tag_utils;
 use super::tag_utils::{ControlFlow, Error, SwfMovie, SwfSlice};
+use super::text::{Font, Text};
 
 #[derive(Debug, Clone)]
 pub struct MovieClip {
@@ -36,6 +42,13 @@ pub struct MovieClip {
     tag_stream_pos: u64,
     depth_list: BTreeMap<Depth, DisplayObject>,
     playing: bool,
+    frame_events: Arc<HashMap<FrameNumber, Vec<FrameEvent>>>,
+    /// 最近一次触发帧事件的帧，避免停在同一帧时重复触发
+    last_event_frame: FrameNumber,
+    /// 推进时遇到的 `StartSound` 与 `StartSound2`，等待取出播放
+    pending_sounds: Vec<StartSound>,
+    /// 按实例名称附加到直接子对象上的置换图滤镜，时间轴重新创建子对象时重新应用
+    displacement_maps: HashMap<Box<str>, DisplacementMapFilter>,
 }
 
 impl MovieClip {
@@ -50,6 +63,10 @@ impl MovieClip {
             tag_stream_pos: 0,
             depth_list: BTreeMap::new(),
             playing: true,
+            frame_events: Arc::default(),
+            last_event_frame: 0,
+            pending_sounds: Vec::new(),
+            displacement_maps: HashMap::new(),
         }
     }
 
@@ -64,6 +81,10 @@ impl MovieClip {
             tag_stream_pos: 0,
             depth_list: BTreeMap::new(),
             playing: true,
+            frame_events: Arc::default(),
+            last_event_frame: 0,
+            pending_sounds: Vec::new(),
+            displacement_maps: HashMap::new(),
         }
     }
 
@@ -83,6 +104,7 @@ impl MovieClip {
     ) {
         let swf = self.swf_slice.clone();
         let mut reader = Reader::new(swf.data(), swf.version());
+        let mut sound_stream = None;
         let tag_callback = |reader: &mut Reader<'_>, tag_code, tag_len| {
             match tag_code {
                 TagCode::DefineShape => {
@@ -103,6 +125,14 @@ impl MovieClip {
                 TagCode::DefineMorphShape2 => {
                     define_morph_shape(library.characters_mut(), self.movie(), reader, 2)
                 }
+                TagCode::DefineFont => define_font_1(library.fonts_mut(), reader),
+                TagCode::DefineFont2 => define_font_2_or_3(library.fonts_mut(), reader, 2),
+                TagCode::DefineFont3 => define_font_2_or_3(library.fonts_mut(), reader, 3),
+                TagCode::DefineText => define_text(library, self.movie(), reader, 1),
+                TagCode::DefineText2 => define_text(library, self.movie(), reader, 2),
+                TagCode::DefineEditText => define_edit_text(library, self.movie(), reader),
+                TagCode::DefineButton => define_button(library, self.movie(), reader, 1),
+                TagCode::DefineButton2 => define_button(library, self.movie(), reader, 2),
                 TagCode::DefineBits => define_bits(bitmaps, jpeg_tables, reader),
                 TagCode::DefineBitsJpeg2 => define_bits_jpeg_2(bitmaps, reader),
                 TagCode::DefineBitsJpeg3 => define_bits_jpeg_3_or_4(bitmaps, reader, 3),
@@ -110,6 +140,18 @@ impl MovieClip {
                 TagCode::DefineBitsLossless => define_bits_lossless(bitmaps, reader, 1),
                 TagCode::DefineBitsLossless2 => define_bits_lossless(bitmaps, reader, 2),
                 TagCode::ExportAssets => export_assets(library.export_characters_mut(), reader),
+                TagCode::SymbolClass => symbol_class(library.export_characters_mut(), reader),
+                TagCode::DefineSound => define_sound(library.sounds_mut(), reader),
+                TagCode::SoundStreamHead | TagCode::SoundStreamHead2 => {
+                    sound_stream_head(&mut sound_stream, reader)
+                }
+                TagCode::SoundStreamBlock => {
+                    // 数据块属于下一个 ShowFrame 所在的帧
+                    if let Some(stream) = &mut sound_stream {
+                        stream.push_block(self.current_frame + 1, reader.read_slice_to_end());
+                    }
+                    Ok(())
+                }
                 TagCode::FrameLabel => self.frame_label(reader, self.current_frame()),
                 TagCode::DefineSprite => {
                     return define_sprite(
@@ -134,6 +176,9 @@ impl MovieClip {
             Ok(ControlFlow::Continue)
         };
         let _ = tag_utils::decode_tags(&mut reader, tag_callback);
+        if let Some(stream) = sound_stream.filter(|stream| !stream.is_empty()) {
+            library.sound_streams_mut().insert(self.id, stream);
+        }
     }
 
     fn frame_label(
@@ -152,14 +197,241 @@ impl MovieClip {
         &self.frame_labels
     }
 
-    pub fn skin_frame(&self) -> HashMap<Box<str>, FrameNumber> {
+    /// 从以 `prefix` 开头的帧标签中收集帧事件
+    pub(crate) fn init_frame_events(&mut self, prefix: &str) {
+        self.frame_events = Arc::new(frame_events_from_labels(&self.frame_labels, prefix));
+    }
+
+    /// 收集子影片当前帧的帧事件，`path` 为子影片的实例路径，未命名的实例使用 `depth_<深度>`
+    pub(crate) fn collect_child_frame_events(
+        &mut self,
+        path: &mut Vec<String>,
+        events: &mut Vec<(String, FrameEvent)>,
+    ) {
+        for (depth, child) in self.depth_list.iter_mut() {
+            let DisplayObject::MovieClip(clip) = child else {
+                continue;
+            };
+            path.push(
+                clip.name()
+                    .map_or_else(|| format!("depth_{depth}"), ToString::to_string),
+            );
+            if clip.current_frame != clip.last_event_frame {
+                clip.last_event_frame = clip.current_frame;
+                if let Some(frame_events) = clip.frame_events.get(&clip.current_frame) {
+                    let path = path.join(".");
+                    events.extend(frame_events.iter().map(|e| (path.clone(), e.clone())));
+                }
+            }
+            clip.collect_child_frame_events(path, events);
+            path.pop();
+        }
+    }
+
+    /// 按指针输入更新按钮状态，`position` 为本影片坐标系下的指针位置，`is_root` 表示根影片。
+    ///
+    /// 按深度从高到低检测，返回是否有按钮的状态发生了变化
+    pub(crate) fn update_buttons(
+        &mut self,
+        characters: &HashMap<CharacterId, Character>,
+        position: Option<Point<Twips>>,
+        context: &mut PointerContext,
+        is_root: bool,
+    ) -> bool {
+        let mut changed = false;
+        for (depth, child) in self.depth_list.iter_mut().rev() {
+            let mut matrix = *child.matrix();
+            if is_root {
+                if context.flip_x {
+                    matrix.a = -matrix.a;
+                }
+                if context.flip_y {
+                    matrix.d = -matrix.d;
+                }
+            }
+            let position = position
+                .filter(|_| !context.hit && child.visible())
+                .and_then(|position| Some(matrix.inverse()? * position));
+            context.path.push(
+                child
+                    .name()
+                    .map_or_else(|| format!("depth_{depth}"), ToString::to_string),
+            );
+            match child {
+                DisplayObject::Button(button) => {
+                    let (state_changed, clicked) =
+                        button.handle_pointer(characters, position, &context.input);
+                    context.hit |= position.is_some_and(|position| button.hit_test(position));
+                    changed |= state_changed;
+                    if clicked {
+                        context.clicked.push(context.path.join("."));
+                    }
+                }
+                DisplayObject::MovieClip(clip) => {
+                    changed |= clip.update_buttons(characters, position, context, false);
+                }
+                _ => {}
+            }
+            context.path.pop();
+        }
+        changed
+    }
+
+    /// 取出本影片及其子对象在推进时遇到的声音，`path` 为本影片的实例路径
+    pub(crate) fn take_sounds(
+        &mut self,
+        path: &mut Vec<String>,
+        sounds: &mut Vec<(String, StartSound)>,
+    ) {
+        if !self.pending_sounds.is_empty() {
+            let instance_path = path.join(".");
+            sounds.extend(
+                self.pending_sounds
+                    .drain(..)
+                    .map(|sound| (instance_path.clone(), sound)),
+            );
+        }
+        take_children_sounds(self.depth_list.values_mut(), path, sounds);
+    }
+
+    /// 收集本影片及其子对象中正在播放、且带有流式声音的影片剪辑，
+    /// 返回 (实例路径, 影片 ID, 当前帧)，`path` 为本影片的实例路径
+    pub(crate) fn collect_sound_streams(
+        &self,
+        has_stream: &impl Fn(CharacterId) -> bool,
+        path: &mut Vec<String>,
+        streams: &mut Vec<(String, CharacterId, FrameNumber)>,
+    ) {
+        if self.playing && has_stream(self.id) {
+            streams.push((path.join("."), self.id, self.current_frame));
+        }
+        collect_children_sound_streams(self.depth_list.values(), has_stream, path, streams);
+    }
+
+    /// 按实例名称查找动态文本并设置文本，递归查找子影片，找到时返回 `true`
+    pub fn set_text(&mut self, name: &str, text: &str) -> bool {
+        for child in self.depth_list.values_mut() {
+            let found = match child {
+                DisplayObject::EditText(edit_text) if edit_text.name() == Some(name) => {
+                    edit_text.set_text(text);
+                    true
+                }
+                DisplayObject::MovieClip(clip) => clip.set_text(name, text),
+                _ => false,
+            };
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 按实例名称查找显示对象并附加置换图滤镜，`None` 时移除，递归查找子影片，找到时返回 `true`。
+    ///
+    /// 滤镜按实例名称记录在父影片上，时间轴重新创建该子对象时会重新应用；
+    /// 如果父影片本身被重新创建，则需要重新设置
+    pub fn set_displacement_map(
+        &mut self,
+        name: &str,
+        displacement_map: Option<DisplacementMapFilter>,
+    ) -> bool {
+        for child in self.depth_list.values_mut() {
+            if child.name() == Some(name) {
+                match &displacement_map {
+                    Some(filter) => {
+                        self.displacement_maps.insert(name.into(), filter.clone());
+                    }
+                    None => {
+                        self.displacement_maps.remove(name);
+                    }
+                }
+                child.set_displacement_map(displacement_map);
+                return true;
+            }
+            if let DisplayObject::MovieClip(clip) = child
+                && clip.set_displacement_map(name, displacement_map.clone())
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 以 `prefix` 开头的皮肤帧标签，返回去掉前缀后的皮肤名称与对应的帧
+    pub fn skin_frame(&self, prefix: &str) -> HashMap<Box<str>, FrameNumber> {
         self.frame_labels
             .iter()
-            .filter(|(k, _)| k.starts_with("skin_"))
-            .map(|(k, v)| (k[5..].into(), *v))
+            .filter_map(|(k, v)| k.strip_prefix(prefix).map(|skin| (skin.into(), *v)))
             .collect()
     }
 
+    /// 是否定义了以 `prefix` 开头的皮肤帧标签
+    fn has_skin(&self, prefix: &str) -> bool {
+        self.frame_labels
+            .keys()
+            .any(|label| label.starts_with(prefix))
+    }
+
+    /// 将所有定义了 `<prefix><skin>` 帧标签的子影片跳转并停在该帧，递归作用于嵌套的影片
+    pub fn apply_skin(
+        &mut self,
+        characters: &HashMap<CharacterId, Character>,
+        prefix: &str,
+        skin: &str,
+    ) {
+        let label = format!("{prefix}{skin}");
+        self.apply_skin_label(characters, &label);
+    }
+
+    fn apply_skin_label(&mut self, characters: &HashMap<CharacterId, Character>, label: &str) {
+        for child in self.depth_list.values_mut() {
+            let DisplayObject::MovieClip(clip) = child else {
+                continue;
+            };
+            if let Some(&frame) = clip.frame_labels.get(label)
+                && (clip.playing || clip.current_frame != frame)
+            {
+                clip.goto_frame(characters, frame, true);
+            }
+            clip.apply_skin_label(characters, label);
+        }
+    }
+
+    /// 释放被皮肤停住的子影片，从第一帧重新播放
+    pub fn release_skin(&mut self, characters: &HashMap<CharacterId, Character>, prefix: &str) {
+        for child in self.depth_list.values_mut() {
+            let DisplayObject::MovieClip(clip) = child else {
+                continue;
+            };
+            if clip.has_skin(prefix) && !clip.playing {
+                clip.goto_frame(characters, 1, false);
+            }
+            clip.release_skin(characters, prefix);
+        }
+    }
+
+    /// 按本影片的当前帧重建子影片的播放进度，递归作用于嵌套的影片。
+    ///
+    /// 跳转只会重建本影片的时间轴，子影片保留跳转前的状态。倒放时子影片无法逐帧回退，
+    /// 因此由经过的帧数推算出子影片的当前帧；被停住的子影片（例如应用了皮肤）保持不变。
+    ///
+    /// 推算的前提是正在播放的子影片从放置帧开始持续循环播放：子影片中途停止、
+    /// 跳转到其他帧或者放置帧之后被替换时，推算出的帧与正向播放时不一致。
+    pub fn sync_children_to_timeline(&mut self, characters: &HashMap<CharacterId, Character>) {
+        let frame = self.current_frame;
+        for child in self.depth_list.values_mut() {
+            let place_frame = child.place_frame();
+            let DisplayObject::MovieClip(clip) = child else {
+                continue;
+            };
+            if clip.playing && clip.total_frames > 0 {
+                let target = frame.saturating_sub(place_frame) % clip.total_frames + 1;
+                clip.goto_frame(characters, target, false);
+            }
+            clip.sync_children_to_timeline(characters);
+        }
+    }
+
     pub fn render_list(&self) -> Values<'_, u16, DisplayObject> {
         self.depth_list.values()
     }
@@ -220,6 +492,8 @@ impl MovieClip {
                 }
                 TagCode::RemoveObject if run_display_actions => self.remove_object(reader, 1),
                 TagCode::RemoveObject2 if run_display_actions => self.remove_object(reader, 2),
+                TagCode::StartSound if run_display_actions => self.start_sound_1(reader),
+                TagCode::StartSound2 if run_display_actions => self.start_sound_2(reader),
                 TagCode::ShowFrame => return Ok(ControlFlow::Exit),
                 _ => Ok(()),
             }?;
@@ -234,6 +508,27 @@ impl MovieClip {
         }
     }
 
+    #[inline]
+    fn start_sound_1(&mut self, reader: &mut Reader<'_>) -> Result<(), Error> {
+        let start_sound = reader.read_start_sound_1()?;
+        self.pending_sounds.push(StartSound {
+            target: SoundTarget::Id(start_sound.id),
+            info: *start_sound.sound_info,
+        });
+        Ok(())
+    }
+
+    #[inline]
+    fn start_sound_2(&mut self, reader: &mut Reader<'_>) -> Result<(), Error> {
+        let class_name = reader.read_str()?.to_string_lossy(reader.encoding());
+        let info = reader.read_sound_info()?;
+        self.pending_sounds.push(StartSound {
+            target: SoundTarget::Class(class_name.into()),
+            info,
+        });
+        Ok(())
+    }
+
     #[inline]
     fn place_object(
         &mut self,
@@ -255,11 +550,11 @@ impl MovieClip {
             }
             swf::PlaceObjectAction::Replace(id) => {
                 let swf_slice = self.swf_slice.clone();
-                let current_frame = self.current_frame();
+                let place_frame = self.current_frame() + 1;
                 if let Some(child) = self.child_by_depth(place_object.depth) {
                     child.replace_with(id, characters);
                     child.apply_place_object(&place_object, swf_slice.version());
-                    child.set_place_frame(current_frame);
+                    child.set_place_frame(place_frame);
                 }
             }
             swf::PlaceObjectAction::Modify => {
@@ -284,11 +579,20 @@ impl MovieClip {
             place_object,
             self.movie(),
             &self.swf_slice,
-            self.current_frame,
+            // 标签属于正在构建的下一帧，与跳转时记录的放置帧保持一致
+            self.current_frame + 1,
         )
     }
 
-    fn replace_at_depth(&mut self, child: DisplayObject, depth: u16) -> Option<DisplayObject> {
+    fn replace_at_depth(&mut self, mut child: DisplayObject, depth: u16) -> Option<DisplayObject> {
+        // 重新创建的子对象沿用之前按实例名称设置的置换图滤镜
+        let displacement_map = child
+            .name()
+            .and_then(|name| self.displacement_maps.get(name))
+            .cloned();
+        if displacement_map.is_some() {
+            child.set_displacement_map(displacement_map);
+        }
         self.depth_list.insert(depth, child)
     }
 
@@ -562,6 +866,53 @@ impl From<MovieClip> for DisplayObject {
     }
 }
 
+/// 取出子对象中的声音，按钮当前状态中的影片剪辑同样会播放声音
+fn take_children_sounds<'a>(
+    children: impl Iterator<Item = &'a mut DisplayObject>,
+    path: &mut Vec<String>,
+    sounds: &mut Vec<(String, StartSound)>,
+) {
+    for child in children {
+        path.push(
+            child
+                .name()
+                .map_or_else(|| format!("depth_{}", child.depth()), ToString::to_string),
+        );
+        match child {
+            DisplayObject::MovieClip(clip) => clip.take_sounds(path, sounds),
+            DisplayObject::Button(button) => {
+                take_children_sounds(button.render_list_mut(), path, sounds)
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+/// 收集子对象中的流式声音，按钮当前状态中的影片剪辑同样会播放
+fn collect_children_sound_streams<'a>(
+    children: impl Iterator<Item = &'a DisplayObject>,
+    has_stream: &impl Fn(CharacterId) -> bool,
+    path: &mut Vec<String>,
+    streams: &mut Vec<(String, CharacterId, FrameNumber)>,
+) {
+    for child in children {
+        path.push(
+            child
+                .name()
+                .map_or_else(|| format!("depth_{}", child.depth()), ToString::to_string),
+        );
+        match child {
+            DisplayObject::MovieClip(clip) => clip.collect_sound_streams(has_stream, path, streams),
+            DisplayObject::Button(button) => {
+                collect_children_sound_streams(button.render_list(), has_stream, path, streams)
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
 /// Indication of what frame `run_frame` should jump to next.
 #[derive(PartialEq, Eq)]
 enum NextFrame {
@@ -613,6 +964,76 @@ fn define_shape(
     Ok(())
 }
 
+#[inline]
+fn define_font_1(
+    fonts: &mut HashMap<CharacterId, Arc<Font>>,
+    reader: &mut Reader,
+) -> Result<(), Error> {
+    let font = reader.read_define_font_1()?;
+    fonts.insert(font.id, Arc::new(Font::from_font_v1(font)));
+    Ok(())
+}
+
+#[inline]
+fn define_font_2_or_3(
+    fonts: &mut HashMap<CharacterId, Arc<Font>>,
+    reader: &mut Reader,
+    version: u8,
+) -> Result<(), Error> {
+    let font = reader.read_define_font_2(version)?;
+    fonts.insert(font.id, Arc::new(Font::from_swf_tag(font)));
+    Ok(())
+}
+
+#[inline]
+fn define_text(
+    library: &mut MovieLibrary,
+    movie: Arc<SwfMovie>,
+    reader: &mut Reader,
+    version: u8,
+) -> Result<(), Error> {
+    let text = reader.read_define_text(version)?;
+    let id = text.id;
+    let text = Text::from_swf_tag(text, library.fonts(), movie);
+    library.characters_mut().insert(id, Character::Text(text));
+    Ok(())
+}
+
+#[inline]
+fn define_edit_text(
+    library: &mut MovieLibrary,
+    movie: Arc<SwfMovie>,
+    reader: &mut Reader,
+) -> Result<(), Error> {
+    let edit_text = reader.read_define_edit_text()?;
+    let id = edit_text.id();
+    let edit_text = EditText::from_swf_tag(edit_text, library.fonts(), movie);
+    library
+        .characters_mut()
+        .insert(id, Character::EditText(edit_text));
+    Ok(())
+}
+
+#[inline]
+fn define_button(
+    library: &mut MovieLibrary,
+    movie: Arc<SwfMovie>,
+    reader: &mut Reader,
+    version: u8,
+) -> Result<(), Error> {
+    let button = if version == 1 {
+        reader.read_define_button_1()?
+    } else {
+        reader.read_define_button_2()?
+    };
+    let id = button.id;
+    let button = Button::from_swf_tag(button, library.characters(), movie);
+    library
+        .characters_mut()
+        .insert(id, Character::Button(button));
+    Ok(())
+}
+
 #[inline]
 fn define_morph_shape(
     characters: &mut HashMap<CharacterId, Character>,
@@ -744,6 +1165,42 @@ fn export_assets(
     Ok(())
 }
 
+#[inline]
+fn symbol_class(
+    export_characters: &mut HashMap<String, CharacterId>,
+    reader: &mut Reader,
+) -> Result<(), Error> {
+    let num_symbols = reader.read_u16()?;
+    for _ in 0..num_symbols {
+        let id = reader.read_u16()?;
+        let name = reader.read_str()?.to_string_lossy(reader.encoding());
+        export_characters.insert(name, id);
+    }
+    Ok(())
+}
+
+#[inline]
+fn define_sound(
+    sounds: &mut HashMap<CharacterId, SoundData>,
+    reader: &mut Reader,
+) -> Result<(), Error> {
+    let sound = reader.read_define_sound()?;
+    if let Some(data) = decode_define_sound(&sound) {
+        sounds.insert(sound.id, data);
+    }
+    Ok(())
+}
+
+#[inline]
+fn sound_stream_head(
+    sound_stream: &mut Option<SoundStream>,
+    reader: &mut Reader,
+) -> Result<(), Error> {
+    let head = reader.read_sound_stream_head()?;
+    *sound_stream = SoundStream::new(&head);
+    Ok(())
+}
+
 #[derive(Debug)]
 pub(crate) struct GotoPlaceObject<'a> {
     frame: FrameNumber,
@@ -776,6 +1233,9 @@ impl<'a> GotoPlaceObject<'a> {
             if place_object.is_bitmap_cached.is_none() {
                 place_object.is_bitmap_cached = Some(Default::default());
             }
+            if place_object.is_visible.is_none() {
+                place_object.is_visible = Some(true);
+            }
             if place_object.background_color.is_none() {
                 place_object.background_color = Some(Color::from_rgba(0));
             }
@@ -833,3 +1293,180 @@ impl<'a> GotoPlaceObject<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::Handle;
+    use swf::{SwfStr, Tag};
+
+    use super::super::filter::{DisplacementMapFilterMode, Filter};
+    use super::*;
+
+    fn define_shape(id: CharacterId) -> Tag<'static> {
+        let bounds = Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(10.0),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(10.0),
+        };
+        Tag::DefineShape(swf::Shape {
+            version: 1,
+            id,
+            shape_bounds: bounds.clone(),
+            edge_bounds: bounds,
+            flags: swf::ShapeFlag::empty(),
+            styles: swf::ShapeStyles {
+                fill_styles: vec![],
+                line_styles: vec![],
+            },
+            shape: vec![],
+        })
+    }
+
+    fn place(depth: Depth, id: CharacterId, name: &'static str) -> Tag<'static> {
+        Tag::PlaceObject(Box::new(swf::PlaceObject {
+            version: 2,
+            action: PlaceObjectAction::Place(id),
+            depth,
+            matrix: None,
+            color_transform: None,
+            ratio: None,
+            name: Some(SwfStr::from_utf8_str(name)),
+            clip_depth: None,
+            class_name: None,
+            filters: None,
+            background_color: None,
+            blend_mode: None,
+            clip_actions: None,
+            has_image: false,
+            is_bitmap_cached: None,
+            is_visible: None,
+            amf_data: None,
+        }))
+    }
+
+    fn remove(depth: Depth) -> Tag<'static> {
+        Tag::RemoveObject(swf::RemoveObject {
+            depth,
+            character_id: None,
+        })
+    }
+
+    fn define_sprite(id: CharacterId, num_frames: FrameNumber) -> Tag<'static> {
+        Tag::DefineSprite(swf::Sprite {
+            id,
+            num_frames,
+            tags: (0..num_frames).map(|_| Tag::ShowFrame).collect(),
+        })
+    }
+
+    fn clip(tags: &[Tag<'_>]) -> (MovieClip, MovieLibrary) {
+        let movie = Arc::new(SwfMovie::from_tags(tags));
+        let mut library = MovieLibrary::default();
+        MovieClip::new(movie.clone()).preload(
+            &mut library,
+            &mut BitmapLibrary::default(),
+            &mut None,
+        );
+        (MovieClip::new(movie), library)
+    }
+
+    fn displacement_map() -> DisplacementMapFilter {
+        DisplacementMapFilter {
+            map_bitmap: Handle::default(),
+            map_point: (0, 0),
+            component_x: 1,
+            component_y: 2,
+            scale_x: 10.0,
+            scale_y: 10.0,
+            mode: DisplacementMapFilterMode::Clamp,
+            color: Color::from_rgba(0),
+            viewscale_x: 1.0,
+            viewscale_y: 1.0,
+        }
+    }
+
+    fn child_filters(clip: &MovieClip, depth: Depth) -> Vec<Filter> {
+        clip.depth_list[&depth].base().filters()
+    }
+
+    #[test]
+    fn displacement_map_survives_child_recreation() {
+        let (mut clip, library) = clip(&[
+            define_shape(1),
+            place(1, 1, "wave"),
+            Tag::ShowFrame,
+            remove(1),
+            Tag::ShowFrame,
+            place(1, 1, "wave"),
+            Tag::ShowFrame,
+        ]);
+        let characters = library.characters();
+        clip.goto_frame(characters, 1, true);
+        assert!(clip.set_displacement_map("wave", Some(displacement_map())));
+
+        clip.goto_frame(characters, 2, true);
+        assert!(clip.depth_list.is_empty());
+        clip.goto_frame(characters, 3, true);
+        assert_eq!(
+            child_filters(&clip, 1),
+            vec![Filter::DisplacementMapFilter(displacement_map())]
+        );
+
+        assert!(clip.set_displacement_map("wave", None));
+        clip.goto_frame(characters, 1, true);
+        assert!(child_filters(&clip, 1).is_empty());
+    }
+
+    fn child_frame(clip: &MovieClip, depth: Depth) -> FrameNumber {
+        match &clip.depth_list[&depth] {
+            DisplayObject::MovieClip(child) => child.current_frame(),
+            child => panic!("expected a movie clip, found {child:?}"),
+        }
+    }
+
+    /// 子影片在根影片的第 2 帧放置，共 3 帧
+    fn clip_with_child() -> (MovieClip, MovieLibrary) {
+        let mut tags = vec![define_sprite(1, 3), Tag::ShowFrame, place(1, 1, "child")];
+        tags.extend((0..4).map(|_| Tag::ShowFrame));
+        clip(&tags)
+    }
+
+    #[test]
+    fn children_record_the_frame_they_are_placed_on() {
+        let (mut clip, library) = clip_with_child();
+        let characters = library.characters();
+        clip.enter_frame(characters);
+        clip.enter_frame(characters);
+        assert_eq!(clip.current_frame(), 2);
+        assert_eq!(clip.depth_list[&1].place_frame(), 2);
+
+        let (mut jumped, library) = clip_with_child();
+        jumped.goto_frame(library.characters(), 4, false);
+        assert_eq!(jumped.depth_list[&1].place_frame(), 2);
+    }
+
+    #[test]
+    fn sync_children_matches_forward_playback() {
+        let (mut clip, library) = clip_with_child();
+        let characters = library.characters();
+        let mut forward = Vec::new();
+        for _ in 0..5 {
+            clip.enter_frame(characters);
+            if clip.depth_list.contains_key(&1) {
+                forward.push(child_frame(&clip, 1));
+            }
+        }
+        assert_eq!(forward, [1, 2, 3, 1]);
+
+        // 倒放回第 4 帧时，子影片应与正向播放到第 4 帧时一致
+        clip.goto_frame(characters, 4, false);
+        clip.sync_children_to_timeline(characters);
+        assert_eq!(child_frame(&clip, 1), 3);
+
+        let (mut jumped, library) = clip_with_child();
+        jumped.goto_frame(library.characters(), 4, false);
+        jumped.sync_children_to_timeline(library.characters());
+        assert_eq!(child_frame(&jumped, 1), 3);
+    }
+}
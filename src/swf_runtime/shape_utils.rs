@@ -566,3 +566,136 @@ pub fn quadratic_curve_bounds(
             Twips::from_pixels(max_y) + radius,
         ))
 }
+
+/// A fill or stroke kept for hit testing, independent of the shape records.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HitPath {
+    Fill {
+        commands: Vec<DrawCommand>,
+        winding_rule: FillRule,
+    },
+    Stroke {
+        commands: Vec<DrawCommand>,
+        width: Twips,
+    },
+}
+
+impl HitPath {
+    /// Collects the fills and strokes of a shape.
+    pub fn from_shape(shape: &Shape) -> Vec<Self> {
+        ShapeConverter::from_shape(shape)
+            .into_commands()
+            .into_iter()
+            .map(|path| match path {
+                DrawPath::Fill {
+                    commands,
+                    winding_rule,
+                    ..
+                } => Self::Fill {
+                    commands,
+                    winding_rule,
+                },
+                DrawPath::Stroke {
+                    style, commands, ..
+                } => Self::Stroke {
+                    commands,
+                    width: style.width(),
+                },
+            })
+            .collect()
+    }
+
+    /// Returns whether `point` is inside the fill or on the stroke.
+    /// Hairline strokes are treated as one pixel wide.
+    pub fn hit_test(&self, point: swf::Point<Twips>) -> bool {
+        let point = to_pixels(point);
+        match self {
+            Self::Fill {
+                commands,
+                winding_rule,
+            } => {
+                let winding: i32 = flatten(commands)
+                    .map(|(start, end)| winding_number(point, start, end))
+                    .sum();
+                match winding_rule {
+                    FillRule::EvenOdd => winding % 2 != 0,
+                    FillRule::NonZero => winding != 0,
+                }
+            }
+            Self::Stroke { commands, width } => {
+                let radius = (*width).max(Twips::ONE_PX).to_pixels() / 2.0;
+                flatten(commands).any(|(start, end)| distance_to_line(point, start, end) <= radius)
+            }
+        }
+    }
+}
+
+/// The number of line segments a quadratic curve is split into for hit testing.
+const HIT_TEST_CURVE_SEGMENTS: u32 = 16;
+
+fn to_pixels(point: swf::Point<Twips>) -> (f64, f64) {
+    (point.x.to_pixels(), point.y.to_pixels())
+}
+
+/// Splits draw commands into straight lines, in pixels.
+fn flatten(commands: &[DrawCommand]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+    let mut cursor = (0.0, 0.0);
+    commands.iter().flat_map(move |command| {
+        let start = cursor;
+        let points = match command {
+            DrawCommand::MoveTo(point) => {
+                cursor = to_pixels(*point);
+                vec![]
+            }
+            DrawCommand::LineTo(point) => {
+                cursor = to_pixels(*point);
+                vec![cursor]
+            }
+            DrawCommand::QuadraticCurveTo { control, anchor } => {
+                let control = to_pixels(*control);
+                cursor = to_pixels(*anchor);
+                (1..=HIT_TEST_CURVE_SEGMENTS)
+                    .map(|i| {
+                        let t = f64::from(i) / f64::from(HIT_TEST_CURVE_SEGMENTS);
+                        let s = 1.0 - t;
+                        (
+                            s * s * start.0 + 2.0 * s * t * control.0 + t * t * cursor.0,
+                            s * s * start.1 + 2.0 * s * t * control.1 + t * t * cursor.1,
+                        )
+                    })
+                    .collect()
+            }
+        };
+        points.into_iter().scan(start, |previous, point| {
+            let line = (*previous, point);
+            *previous = point;
+            Some(line)
+        })
+    })
+}
+
+/// The winding contribution of a line for a ray cast from `point` towards positive x.
+fn winding_number(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> i32 {
+    if (start.1 <= point.1) == (end.1 <= point.1) {
+        return 0;
+    }
+    let x = start.0 + (point.1 - start.1) * (end.0 - start.0) / (end.1 - start.1);
+    if x <= point.0 {
+        0
+    } else if end.1 > start.1 {
+        1
+    } else {
+        -1
+    }
+}
+
+fn distance_to_line(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point.0 - (start.0 + t * dx)).hypot(point.1 - (start.1 + t * dy))
+}
@@ -0,0 +1,540 @@
+use std::collections::BTreeMap;
+
+use bevy::log::warn_once;
+use swf::{
+    AudioCompression, CharacterId, SoundEnvelopePoint, SoundFormat, SoundInfo, SoundStreamHead,
+};
+
+use super::display_object::FrameNumber;
+
+/// 音量包络、入点与出点使用的采样率
+const ENVELOPE_SAMPLE_RATE: u64 = 44100;
+
+/// 每个 ADPCM 数据块中每个声道的采样数，包括块头中的初始采样
+const ADPCM_BLOCK_SAMPLES: usize = 4096;
+
+const ADPCM_INDEX_TABLES: [&[i32]; 4] = [
+    &[-1, 2],
+    &[-1, -1, 2, 4],
+    &[-1, -1, -1, -1, 2, 4, 6, 8],
+    &[-1, -1, -1, -1, -1, -1, -1, -1, 1, 2, 4, 6, 8, 10, 13, 16],
+];
+
+const ADPCM_STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+/// 解码后的 16 位 PCM，多声道时交错存放
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pcm {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+/// `DefineSound` 中的声音数据
+#[derive(Debug, Clone)]
+pub enum SoundData {
+    Pcm(Pcm),
+    /// MP3 帧，由 Bevy 的音频解码器解码
+    Mp3(Vec<u8>),
+}
+
+/// 解码 `DefineSound`，不支持的压缩格式返回 `None`
+pub fn decode_define_sound(sound: &swf::Sound) -> Option<SoundData> {
+    let format = &sound.format;
+    let channels = if format.is_stereo { 2 } else { 1 };
+    let mut samples = match format.compression {
+        AudioCompression::Uncompressed | AudioCompression::UncompressedUnknownEndian => {
+            decode_uncompressed(sound.data, format.is_16_bit)
+        }
+        AudioCompression::Adpcm => decode_adpcm(sound.data, channels),
+        AudioCompression::Mp3 => return decode_mp3(sound),
+        compression => {
+            warn_once!(
+                "Sound {} uses unsupported compression {:?}",
+                sound.id,
+                compression
+            );
+            return None;
+        }
+    };
+    samples.truncate(sound.num_samples as usize * channels);
+    Some(SoundData::Pcm(Pcm {
+        sample_rate: u32::from(format.sample_rate),
+        channels: channels as u16,
+        samples,
+    }))
+}
+
+/// `DefineSound` 中的 MP3 数据以 2 字节的 SeekSamples 开头
+#[cfg(feature = "mp3")]
+fn decode_mp3(sound: &swf::Sound) -> Option<SoundData> {
+    sound
+        .data
+        .get(2..)
+        .map(|data| SoundData::Mp3(data.to_vec()))
+}
+
+#[cfg(not(feature = "mp3"))]
+fn decode_mp3(sound: &swf::Sound) -> Option<SoundData> {
+    warn_once!(
+        "Sound {} is MP3 encoded, enable the `mp3` feature to play it",
+        sound.id
+    );
+    None
+}
+
+/// 8 位数据为无符号数，统一转换为 16 位有符号采样
+fn decode_uncompressed(data: &[u8], is_16_bit: bool) -> Vec<i16> {
+    if is_16_bit {
+        data.chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect()
+    } else {
+        data.iter()
+            .map(|&byte| (i16::from(byte) - 128) << 8)
+            .collect()
+    }
+}
+
+/// 影片剪辑时间轴上的流式声音（SoundStreamHead / SoundStreamBlock），
+/// 所有数据块拼接为一条音轨，并记录每个数据块所在的帧
+#[derive(Debug, Clone)]
+pub struct SoundStream {
+    format: SoundFormat,
+    data: SoundData,
+    /// 数据块所在的帧与数据块在音轨中的起始位置，PCM 为采样帧，MP3 为字节偏移
+    blocks: BTreeMap<FrameNumber, usize>,
+}
+
+impl SoundStream {
+    /// 不支持的压缩格式返回 `None`
+    pub fn new(head: &SoundStreamHead) -> Option<Self> {
+        let format = &head.stream_format;
+        let data = match format.compression {
+            AudioCompression::Uncompressed
+            | AudioCompression::UncompressedUnknownEndian
+            | AudioCompression::Adpcm => SoundData::Pcm(Pcm {
+                sample_rate: u32::from(format.sample_rate),
+                channels: if format.is_stereo { 2 } else { 1 },
+                samples: Vec::new(),
+            }),
+            AudioCompression::Mp3 if cfg!(feature = "mp3") => SoundData::Mp3(Vec::new()),
+            AudioCompression::Mp3 => {
+                warn_once!("Sound stream is MP3 encoded, enable the `mp3` feature to play it");
+                return None;
+            }
+            compression => {
+                warn_once!("Sound stream uses unsupported compression {compression:?}");
+                return None;
+            }
+        };
+        Some(Self {
+            format: format.clone(),
+            data,
+            blocks: BTreeMap::new(),
+        })
+    }
+
+    /// 追加 `frame` 帧的 SoundStreamBlock，每个 ADPCM 数据块单独编码
+    pub fn push_block(&mut self, frame: FrameNumber, data: &[u8]) {
+        let offset = match &mut self.data {
+            SoundData::Pcm(pcm) => {
+                let offset = pcm.frames();
+                let samples = match self.format.compression {
+                    AudioCompression::Adpcm => decode_adpcm(data, usize::from(pcm.channels)),
+                    _ => decode_uncompressed(data, self.format.is_16_bit),
+                };
+                pcm.samples.extend(samples);
+                offset
+            }
+            // 数据块以 2 字节的采样数与 2 字节的 SeekSamples 开头
+            SoundData::Mp3(mp3) => {
+                let offset = mp3.len();
+                mp3.extend_from_slice(data.get(4..).unwrap_or_default());
+                offset
+            }
+        };
+        self.blocks.entry(frame).or_insert(offset);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match &self.data {
+            SoundData::Pcm(pcm) => pcm.samples.is_empty(),
+            SoundData::Mp3(mp3) => mp3.is_empty(),
+        }
+    }
+
+    /// `frame` 帧对应的音轨位置，即之前最近的数据块的起始位置，
+    /// 不在第一个与最后一个数据块之间时返回 `None`
+    pub fn offset_at(&self, frame: FrameNumber) -> Option<usize> {
+        let (&last, _) = self.blocks.last_key_value()?;
+        if frame > last {
+            return None;
+        }
+        self.blocks
+            .range(..=frame)
+            .next_back()
+            .map(|(_, &offset)| offset)
+    }
+
+    /// 从 `offset` 开始的音轨，编码为 Bevy 的音频解码器可以读取的格式
+    pub fn audio_from(&self, offset: usize) -> Vec<u8> {
+        match &self.data {
+            SoundData::Pcm(pcm) => {
+                let start = (offset * usize::from(pcm.channels)).min(pcm.samples.len());
+                Pcm {
+                    sample_rate: pcm.sample_rate,
+                    channels: pcm.channels,
+                    samples: pcm.samples[start..].to_vec(),
+                }
+                .to_wav()
+            }
+            SoundData::Mp3(mp3) => mp3[offset.min(mp3.len())..].to_vec(),
+        }
+    }
+}
+
+/// 按位读取，高位在前
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u32) -> Option<u32> {
+        if self.position + bits as usize > self.data.len() * 8 {
+            return None;
+        }
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.data[self.position / 8];
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | u32::from(bit);
+            self.position += 1;
+        }
+        Some(value)
+    }
+
+    fn read_signed(&mut self, bits: u32) -> Option<i32> {
+        let shift = 32 - bits;
+        self.read(bits)
+            .map(|value| ((value << shift) as i32) >> shift)
+    }
+}
+
+/// 解码 SWF 的 ADPCM 数据，数据不完整时返回已经解码的部分
+fn decode_adpcm(data: &[u8], channels: usize) -> Vec<i16> {
+    let mut reader = BitReader { data, position: 0 };
+    let Some(bits) = reader.read(2).map(|bits| bits + 2) else {
+        return Vec::new();
+    };
+    let index_table = ADPCM_INDEX_TABLES[bits as usize - 2];
+    let sign_mask = 1 << (bits - 1);
+    let mut samples = Vec::new();
+    // 每个声道的 (采样值, 步长索引)
+    let mut states = [(0i32, 0i32); 2];
+    'blocks: loop {
+        for state in &mut states[..channels] {
+            let (Some(sample), Some(index)) = (reader.read_signed(16), reader.read(6)) else {
+                break 'blocks;
+            };
+            *state = (sample, index.min(88) as i32);
+            samples.push(sample as i16);
+        }
+        for _ in 1..ADPCM_BLOCK_SAMPLES {
+            for (sample, index) in &mut states[..channels] {
+                let Some(code) = reader.read(bits) else {
+                    break 'blocks;
+                };
+                // 符号与数值分开存放：(magnitude + 0.5) * step / 2^(bits - 2)
+                let magnitude = (code & !sign_mask) as usize;
+                let step = ADPCM_STEP_TABLE[*index as usize];
+                let mut delta = ((2 * magnitude as i32 + 1) * step) >> (bits - 1);
+                if code & sign_mask != 0 {
+                    delta = -delta;
+                }
+                *sample = (*sample + delta).clamp(i32::from(i16::MIN), i32::from(i16::MAX));
+                *index = (*index + index_table[magnitude]).clamp(0, 88);
+                samples.push(*sample as i16);
+            }
+        }
+    }
+    // 数据在一帧中间结束时丢弃不完整的一帧
+    samples.truncate(samples.len() / channels * channels);
+    samples
+}
+
+impl Pcm {
+    /// 编码为 WAV，供 Bevy 的音频解码器使用
+    pub fn to_wav(&self) -> Vec<u8> {
+        let data_len = (self.samples.len() * 2) as u32;
+        let block_align = self.channels * 2;
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // PCM 格式
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&self.channels.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(self.sample_rate * u32::from(block_align)).to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    fn frames(&self) -> usize {
+        self.samples.len() / usize::from(self.channels)
+    }
+
+    /// 按 `SoundInfo` 截取入点与出点并应用音量包络，生成一轮播放的 PCM。
+    ///
+    /// 循环次数不会烘焙到 PCM 中，播放完一轮后重新播放，因此包络作用于每一轮
+    pub fn apply_sound_info(&self, info: &SoundInfo) -> Pcm {
+        let channels = usize::from(self.channels);
+        let frames = self.frames();
+        let to_frame = |sample: u32| {
+            (u64::from(sample) * u64::from(self.sample_rate) / ENVELOPE_SAMPLE_RATE) as usize
+        };
+        let start = info.in_sample.map_or(0, to_frame).min(frames);
+        let end = info
+            .out_sample
+            .map_or(frames, to_frame)
+            .clamp(start, frames);
+        let mut pcm = Pcm {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            samples: self.samples[start * channels..end * channels].to_vec(),
+        };
+        if let Some(envelope) = info.envelope.as_deref()
+            && !envelope.is_empty()
+        {
+            pcm.apply_envelope(envelope);
+        }
+        pcm
+    }
+
+    /// 应用左右声道的音量包络，单声道会先转换为立体声
+    fn apply_envelope(&mut self, envelope: &[SoundEnvelopePoint]) {
+        if self.channels == 1 {
+            self.samples = self
+                .samples
+                .iter()
+                .flat_map(|&sample| [sample, sample])
+                .collect();
+            self.channels = 2;
+        }
+        let sample_rate = u64::from(self.sample_rate);
+        for (frame, samples) in self.samples.chunks_exact_mut(2).enumerate() {
+            let position = frame as u64 * ENVELOPE_SAMPLE_RATE / sample_rate;
+            let (left, right) = envelope_volume(envelope, position as u32);
+            samples[0] = (f32::from(samples[0]) * left) as i16;
+            samples[1] = (f32::from(samples[1]) * right) as i16;
+        }
+    }
+}
+
+/// 包络在 `position`（44.1kHz 采样）处的左右声道音量，两个控制点之间线性插值
+fn envelope_volume(envelope: &[SoundEnvelopePoint], position: u32) -> (f32, f32) {
+    let next = envelope.iter().position(|point| point.sample > position);
+    match next {
+        Some(0) => (envelope[0].left_volume, envelope[0].right_volume),
+        Some(index) => {
+            let (prev, next) = (&envelope[index - 1], &envelope[index]);
+            let t = (position - prev.sample) as f32 / (next.sample - prev.sample) as f32;
+            (
+                prev.left_volume + (next.left_volume - prev.left_volume) * t,
+                prev.right_volume + (next.right_volume - prev.right_volume) * t,
+            )
+        }
+        None => {
+            let last = &envelope[envelope.len() - 1];
+            (last.left_volume, last.right_volume)
+        }
+    }
+}
+
+/// `StartSound` 引用的声音，`StartSound2` 通过导出的类名引用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoundTarget {
+    Id(CharacterId),
+    Class(Box<str>),
+}
+
+/// 生成声音变体用到的 `SoundInfo` 部分，即入点、出点与音量包络，用作变体缓存的键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SoundVariant {
+    in_sample: Option<u32>,
+    out_sample: Option<u32>,
+    /// 包络控制点的位置与左右声道音量的位表示
+    envelope: Vec<(u32, u32, u32)>,
+}
+
+impl SoundVariant {
+    /// 是否与原始声音相同，不需要生成变体
+    pub fn is_original(&self) -> bool {
+        self.in_sample.is_none() && self.out_sample.is_none() && self.envelope.is_empty()
+    }
+}
+
+impl From<&SoundInfo> for SoundVariant {
+    fn from(info: &SoundInfo) -> Self {
+        Self {
+            in_sample: info.in_sample,
+            out_sample: info.out_sample,
+            envelope: info
+                .envelope
+                .iter()
+                .flatten()
+                .map(|point| {
+                    (
+                        point.sample,
+                        point.left_volume.to_bits(),
+                        point.right_volume.to_bits(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 时间轴上遇到的 `StartSound` / `StartSound2`
+#[derive(Debug, Clone)]
+pub struct StartSound {
+    pub target: SoundTarget,
+    pub info: SoundInfo,
+}
+
+#[cfg(test)]
+mod tests {
+    use swf::SoundEvent;
+
+    use super::*;
+
+    fn sound_info() -> SoundInfo {
+        SoundInfo {
+            event: SoundEvent::Event,
+            in_sample: None,
+            out_sample: None,
+            num_loops: 1,
+            envelope: None,
+        }
+    }
+
+    fn pcm(samples: Vec<i16>, channels: u16) -> Pcm {
+        Pcm {
+            sample_rate: 44100,
+            channels,
+            samples,
+        }
+    }
+
+    #[test]
+    fn wav_header_describes_pcm() {
+        let wav = pcm(vec![1, -1, 2, -2], 2).to_wav();
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]), 8);
+        assert_eq!(i16::from_le_bytes([wav[46], wav[47]]), -1);
+    }
+
+    #[test]
+    fn sound_info_trims_one_loop() {
+        let info = SoundInfo {
+            in_sample: Some(1),
+            out_sample: Some(3),
+            num_loops: 3,
+            ..sound_info()
+        };
+        let baked = pcm(vec![0, 1, 2, 3, 4], 1).apply_sound_info(&info);
+        assert_eq!(baked.samples, vec![1, 2]);
+    }
+
+    #[test]
+    fn loop_count_does_not_change_variant() {
+        let looped = SoundInfo {
+            num_loops: 5,
+            ..sound_info()
+        };
+        assert!(SoundVariant::from(&looped).is_original());
+        let trimmed = SoundInfo {
+            in_sample: Some(10),
+            ..looped.clone()
+        };
+        assert_ne!(SoundVariant::from(&trimmed), SoundVariant::from(&looped));
+    }
+
+    #[test]
+    fn envelope_pans_mono_sound() {
+        let info = SoundInfo {
+            envelope: Some(vec![
+                SoundEnvelopePoint {
+                    sample: 0,
+                    left_volume: 1.0,
+                    right_volume: 0.0,
+                },
+                SoundEnvelopePoint {
+                    sample: 2,
+                    left_volume: 0.0,
+                    right_volume: 1.0,
+                },
+            ]),
+            ..sound_info()
+        };
+        let baked = pcm(vec![1000; 3], 1).apply_sound_info(&info);
+        assert_eq!(baked.channels, 2);
+        assert_eq!(baked.samples, vec![1000, 0, 500, 500, 0, 1000]);
+    }
+
+    #[test]
+    fn adpcm_block_header_is_first_sample() {
+        // 2 位编码，初始采样 0x0100，步长索引 0，之后一个编码 0b01
+        let data = [0b0000_0000, 0b0100_0000, 0b0000_0000, 0b0100_0000];
+        let samples = decode_adpcm(&data, 1);
+        assert_eq!(samples[0], 256);
+        // (2 * 1 + 1) * 7 >> 1 = 10
+        assert_eq!(samples[1], 266);
+    }
+
+    #[test]
+    fn sound_stream_seeks_to_block_of_frame() {
+        let format = SoundFormat {
+            compression: AudioCompression::Uncompressed,
+            sample_rate: 44100,
+            is_stereo: false,
+            is_16_bit: true,
+        };
+        let head = SoundStreamHead {
+            stream_format: format.clone(),
+            playback_format: format,
+            num_samples_per_block: 2,
+            latency_seek: 0,
+        };
+        let mut stream = SoundStream::new(&head).unwrap();
+        stream.push_block(2, &[1, 0, 2, 0]);
+        stream.push_block(4, &[3, 0, 4, 0]);
+
+        assert_eq!(stream.offset_at(1), None);
+        assert_eq!(stream.offset_at(2), Some(0));
+        assert_eq!(stream.offset_at(3), Some(0));
+        assert_eq!(stream.offset_at(4), Some(2));
+        assert_eq!(stream.offset_at(5), None);
+
+        let wav = stream.audio_from(2);
+        assert_eq!(wav.len(), 44 + 4);
+        assert_eq!(i16::from_le_bytes([wav[44], wav[45]]), 3);
+    }
+}
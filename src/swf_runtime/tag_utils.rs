@@ -51,6 +51,20 @@ impl SwfMovie {
     pub fn version(&self) -> u8 {
         self.header.version()
     }
+
+    /// 由标签构建测试用的影片，帧数为 `ShowFrame` 标签的数量
+    #[cfg(test)]
+    pub(crate) fn from_tags(tags: &[swf::Tag<'_>]) -> Self {
+        let mut header = swf::Header::default_with_swf_version(10);
+        header.frame_rate = swf::Fixed8::from_f32(24.0);
+        header.num_frames = tags
+            .iter()
+            .filter(|tag| matches!(tag, swf::Tag::ShowFrame))
+            .count() as u16;
+        let mut data = Vec::new();
+        swf::write_swf(&header, tags, &mut data).unwrap();
+        Self::from_data(&data).unwrap()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,3 +1,4 @@
+use bevy::math::Vec2;
 use bevy::platform::collections::HashMap;
 use bevy::prelude::error;
 use indexmap::IndexSet;
@@ -8,9 +9,9 @@ use lyon_tessellation::{
 };
 use lyon_tessellation::{FillVertex, FillVertexConstructor, StrokeVertex, StrokeVertexConstructor};
 
-use swf::CharacterId;
+use swf::{CharacterId, LineStyle, ShapeRecord};
 
-use super::character::CompressedBitmap;
+use crate::assets::BitmapTexture;
 
 use super::matrix::Matrix;
 use super::shape_utils::{DistilledShape, DrawCommand, DrawPath, GradientType};
@@ -23,10 +24,19 @@ pub struct ShapeTessellator {
     lyon_mesh: VertexBuffers<Vertex, u32>,
     mask_index_count: Option<u32>,
     is_stroke: bool,
+    /// 曲线细分的容差（像素），越小曲线越平滑，顶点也越多
+    tolerance: f32,
 }
 
 impl Default for ShapeTessellator {
     fn default() -> Self {
+        Self::new(FillOptions::DEFAULT_TOLERANCE)
+    }
+}
+
+impl ShapeTessellator {
+    /// 使用指定的曲线细分容差（像素）
+    pub fn new(tolerance: f32) -> Self {
         Self {
             fill_tess: FillTessellator::new(),
             stroke_tess: StrokeTessellator::new(),
@@ -35,15 +45,17 @@ impl Default for ShapeTessellator {
             lyon_mesh: VertexBuffers::new(),
             mask_index_count: None,
             is_stroke: false,
+            tolerance,
         }
     }
-}
 
-impl ShapeTessellator {
+    /// 细分形状，`stroke_transform` 为形状局部坐标到屏幕像素的变换，
+    /// 不缩放线条、细线和像素对齐需要根据它计算线条宽度与位置
     pub fn tessellate_shape(
         &mut self,
         shape: DistilledShape,
-        bitmaps: &HashMap<CharacterId, CompressedBitmap>,
+        bitmaps: &HashMap<CharacterId, BitmapTexture>,
+        stroke_transform: StrokeTransform,
     ) -> Mesh {
         self.mesh = Vec::new();
         self.gradients = IndexSet::new();
@@ -55,16 +67,28 @@ impl ShapeTessellator {
                     style,
                     commands,
                     winding_rule: _,
-                } => (*style, ruffle_path_to_lyon_path(commands, true), false),
+                } => (
+                    *style,
+                    ruffle_path_to_lyon_path(commands, true, None),
+                    false,
+                ),
                 DrawPath::Stroke {
                     style,
                     commands,
                     is_closed,
-                } => (
-                    style.fill_style(),
-                    ruffle_path_to_lyon_path(commands, *is_closed),
-                    true,
-                ),
+                } => {
+                    let hinting = style.is_pixel_hinted().then(|| {
+                        PixelHinting::new(
+                            stroke_width(style, stroke_transform.scale),
+                            stroke_transform,
+                        )
+                    });
+                    (
+                        style.fill_style(),
+                        ruffle_path_to_lyon_path(commands, *is_closed, hinting),
+                        true,
+                    )
+                }
             };
 
             let (draw, color, needs_flush) = match fill_style {
@@ -118,16 +142,13 @@ impl ShapeTessellator {
                     is_smoothed,
                     is_repeating,
                 } => {
-                    let bitmap_size = bitmaps
-                        .get(id)
-                        .map(|compressed_bitmap| compressed_bitmap.size());
-                    if let Some(bitmap_size) = bitmap_size {
+                    if let Some(bitmap) = bitmaps.get(id) {
                         (
                             DrawType::Bitmap(Bitmap {
                                 matrix: swf_bitmap_to_gl_matrix(
                                     (*matrix).into(),
-                                    bitmap_size.width.into(),
-                                    bitmap_size.height.into(),
+                                    bitmap.width.into(),
+                                    bitmap.height.into(),
                                 ),
                                 bitmap_id: *id,
                                 is_smoothed: *is_smoothed,
@@ -161,12 +182,15 @@ impl ShapeTessellator {
             let result = match path {
                 DrawPath::Fill { winding_rule, .. } => self.fill_tess.tessellate_path(
                     &lyon_path,
-                    &FillOptions::default().with_fill_rule(winding_rule.into()),
+                    &FillOptions::default()
+                        .with_fill_rule(winding_rule.into())
+                        .with_tolerance(self.tolerance),
                     &mut buffers_builder,
                 ),
                 DrawPath::Stroke { style, .. } => {
-                    let width = (style.width().to_pixels() as f32).max(1.0);
+                    let width = stroke_width(style, stroke_transform.scale);
                     let mut stroke_options = StrokeOptions::default()
+                        .with_tolerance(self.tolerance)
                         .with_line_width(width)
                         .with_start_cap(match style.start_cap() {
                             swf::LineCapStyle::None => lyon_tessellation::LineCap::Butt,
@@ -294,11 +318,112 @@ pub struct Bitmap {
     pub is_repeating: bool,
 }
 
-fn ruffle_path_to_lyon_path(commands: &[DrawCommand], is_closed: bool) -> Path {
-    fn point(point: swf::Point<swf::Twips>) -> Point {
-        Point::new(point.x.to_pixels() as f32, point.y.to_pixels() as f32)
+/// 形状局部坐标（像素）到屏幕像素的变换，只包含轴向的缩放与平移。
+///
+/// 旋转与斜切时使用各轴的缩放长度近似，平移以根影片原点对齐到屏幕像素为前提。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeTransform {
+    pub scale: Vec2,
+    pub translation: Vec2,
+}
+
+impl StrokeTransform {
+    /// 加载时细分静态形状使用的变换
+    pub const IDENTITY: Self = Self {
+        scale: Vec2::ONE,
+        translation: Vec2::ZERO,
+    };
+}
+
+/// 形状中是否存在满足 `predicate` 的线条样式，包括样式变更记录中新增的样式
+fn any_line_style(shape: &swf::Shape, predicate: impl Fn(&LineStyle) -> bool) -> bool {
+    shape.styles.line_styles.iter().any(&predicate)
+        || shape.shape.iter().any(|record| match record {
+            ShapeRecord::StyleChange(style_change) => style_change
+                .new_styles
+                .as_ref()
+                .is_some_and(|styles| styles.line_styles.iter().any(&predicate)),
+            _ => false,
+        })
+}
+
+/// 形状中是否存在宽度依赖于屏幕缩放的线条（不缩放线条、细线、像素对齐），
+/// 这类形状需要在渲染时按当前缩放重新细分
+pub fn has_scale_dependent_strokes(shape: &swf::Shape) -> bool {
+    any_line_style(shape, |style| {
+        !style.allow_scale_x()
+            || !style.allow_scale_y()
+            || style.width() == swf::Twips::ZERO
+            || style.is_pixel_hinted()
+    })
+}
+
+/// 形状中是否存在像素对齐的线条，这类形状还需要按屏幕平移的小数部分重新细分
+pub fn has_pixel_hinted_strokes(shape: &swf::Shape) -> bool {
+    any_line_style(shape, LineStyle::is_pixel_hinted)
+}
+
+/// 计算线条在形状局部坐标系下的宽度（像素）。
+///
+/// 不允许缩放的方向上线条宽度需要抵消该方向的缩放，屏幕上的宽度最小为 1 像素，
+/// 宽度为 0 的细线因此始终为 1 像素宽。
+fn stroke_width(style: &LineStyle, stroke_scale: Vec2) -> f32 {
+    let width = style.width().to_pixels() as f32;
+    let scale = (stroke_scale.x + stroke_scale.y) / 2.0;
+    if scale <= f32::EPSILON {
+        return width.max(1.0);
+    }
+    let no_scale = match (style.allow_scale_x(), style.allow_scale_y()) {
+        (true, true) => 1.0,
+        (true, false) => stroke_scale.y,
+        (false, true) => stroke_scale.x,
+        (false, false) => scale,
+    };
+    let screen_width = (width * scale / no_scale.max(f32::EPSILON)).max(1.0);
+    screen_width / scale
+}
+
+/// 像素对齐，将线条的点变换到屏幕空间后对齐到像素网格上，再变换回形状局部坐标。
+/// 奇数像素宽的线条对齐到像素中心，否则对齐到像素边缘。
+#[derive(Clone, Copy)]
+struct PixelHinting {
+    scale: Vec2,
+    translation: Vec2,
+    offset: f32,
+}
+
+impl PixelHinting {
+    fn new(width: f32, transform: StrokeTransform) -> Self {
+        let scale = transform.scale;
+        let screen_width = (width * (scale.x + scale.y) / 2.0).round() as i32;
+        Self {
+            scale: scale.max(Vec2::splat(f32::EPSILON)),
+            translation: transform.translation,
+            offset: if screen_width % 2 == 1 { 0.5 } else { 0.0 },
+        }
     }
 
+    fn snap(&self, point: Point) -> Point {
+        let screen = Vec2::new(point.x, point.y) * self.scale + self.translation;
+        let snapped = screen.round() + Vec2::splat(self.offset);
+        let local = (snapped - self.translation) / self.scale;
+        Point::new(local.x, local.y)
+    }
+}
+
+fn ruffle_path_to_lyon_path(
+    commands: &[DrawCommand],
+    is_closed: bool,
+    hinting: Option<PixelHinting>,
+) -> Path {
+    let point = |point: swf::Point<swf::Twips>| {
+        let point = Point::new(point.x.to_pixels() as f32, point.y.to_pixels() as f32);
+        match hinting {
+            Some(hinting) => hinting.snap(point),
+            None => point,
+        }
+    };
+
     let mut builder = Path::builder();
     let mut cursor = Some(swf::Point::ZERO);
     for command in commands {
@@ -423,3 +548,36 @@ impl StrokeVertexConstructor<Vertex> for RuffleVertexCtor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_hinting_snaps_in_screen_space() {
+        let hinting = PixelHinting::new(
+            1.0,
+            StrokeTransform {
+                scale: Vec2::splat(2.0),
+                translation: Vec2::new(0.25, 0.5),
+            },
+        );
+        let snapped = hinting.snap(Point::new(1.1, 2.3));
+        // 屏幕坐标 (2.45, 5.1) 对齐到 2 像素宽线条的像素边缘 (2, 5)
+        assert!((snapped.x * 2.0 + 0.25 - 2.0).abs() < 1e-5);
+        assert!((snapped.y * 2.0 + 0.5 - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn odd_width_hinting_snaps_to_pixel_centers() {
+        let hinting = PixelHinting::new(
+            1.0,
+            StrokeTransform {
+                scale: Vec2::ONE,
+                translation: Vec2::new(0.75, 0.0),
+            },
+        );
+        let snapped = hinting.snap(Point::new(3.0, 0.0));
+        assert!((snapped.x + 0.75 - 4.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use bevy::asset::Handle;
+use bevy::platform::collections::HashMap;
+use swf::{
+    BlendMode, CharacterId, Color, ColorTransform, FillStyle, Rectangle, ShapeFlag, ShapeRecord,
+    ShapeStyles, Twips,
+};
+
+use crate::RenderContext;
+use crate::assets::Shape;
+
+use super::character::Character;
+use super::display_object::{DisplayObject, DisplayObjectBase, TDisplayObject};
+use super::matrix::Matrix;
+use super::tag_utils::SwfMovie;
+use super::transform::Transform;
+
+/// 字形句柄，key 为 (字体 ID, 字形索引)
+pub(crate) type GlyphShapes = HashMap<(CharacterId, u32), Handle<Shape>>;
+
+/// 嵌入字体，保存每个字形的轮廓
+#[derive(Debug, Clone)]
+pub struct Font {
+    glyphs: Vec<swf::Shape>,
+    /// 字形坐标所在的 EM 方框大小，DefineFont3 的精度是之前版本的 20 倍
+    scale: f32,
+    /// 字符编码与字形索引的映射，DefineFont 没有编码信息
+    code_points: HashMap<u16, u32>,
+    /// 字形的前进宽度，没有布局信息时为空
+    advances: Vec<i16>,
+    ascent: u16,
+    descent: u16,
+}
+
+impl Font {
+    pub fn from_font_v1(font: swf::FontV1) -> Self {
+        Self {
+            glyphs: font
+                .glyphs
+                .into_iter()
+                .map(|records| glyph_shape(records, None))
+                .collect(),
+            scale: 1024.0,
+            code_points: HashMap::new(),
+            advances: Vec::new(),
+            ascent: 0,
+            descent: 0,
+        }
+    }
+
+    pub fn from_swf_tag(font: swf::Font) -> Self {
+        let (ascent, descent) = font
+            .layout
+            .as_ref()
+            .map_or((0, 0), |layout| (layout.ascent, layout.descent));
+        let has_layout = font.layout.is_some();
+        let mut code_points = HashMap::new();
+        let mut advances = Vec::new();
+        let mut glyphs = Vec::with_capacity(font.glyphs.len());
+        for (index, glyph) in font.glyphs.into_iter().enumerate() {
+            code_points.insert(glyph.code, index as u32);
+            if has_layout {
+                advances.push(glyph.advance);
+            }
+            glyphs.push(glyph_shape(glyph.shape_records, glyph.bounds));
+        }
+        Self {
+            scale: if font.version >= 3 { 20480.0 } else { 1024.0 },
+            glyphs,
+            code_points,
+            advances,
+            ascent,
+            descent,
+        }
+    }
+
+    pub fn glyph(&self, index: u32) -> Option<&swf::Shape> {
+        self.glyphs.get(index as usize)
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn glyph_count(&self) -> u32 {
+        self.glyphs.len() as u32
+    }
+
+    /// 字符对应的字形索引
+    pub fn glyph_index(&self, c: char) -> Option<u32> {
+        let code = u16::try_from(u32::from(c)).ok()?;
+        self.code_points.get(&code).copied()
+    }
+
+    /// 字形的前进宽度（EM 单位），没有布局信息时使用半个 EM
+    pub fn advance(&self, index: u32) -> f32 {
+        self.advances
+            .get(index as usize)
+            .map_or(self.scale / 2.0, |advance| f32::from(*advance))
+    }
+
+    /// 基线以上的高度（EM 单位），没有布局信息时使用 0.8 个 EM
+    pub fn ascent(&self) -> f32 {
+        if self.ascent == 0 {
+            self.scale * 0.8
+        } else {
+            f32::from(self.ascent)
+        }
+    }
+
+    /// 基线以下的高度（EM 单位），没有布局信息时使用 0.2 个 EM
+    pub fn descent(&self) -> f32 {
+        if self.descent == 0 {
+            self.scale * 0.2
+        } else {
+            f32::from(self.descent)
+        }
+    }
+}
+
+/// 字形轮廓使用白色填充，文本颜色通过颜色变换相乘得到
+fn glyph_shape(records: Vec<ShapeRecord>, bounds: Option<Rectangle<Twips>>) -> swf::Shape {
+    let bounds = bounds.unwrap_or_default();
+    swf::Shape {
+        version: 2,
+        id: 0,
+        shape_bounds: bounds.clone(),
+        edge_bounds: bounds,
+        flags: ShapeFlag::HAS_SCALING_STROKES,
+        styles: ShapeStyles {
+            fill_styles: vec![FillStyle::Color(Color::WHITE)],
+            line_styles: vec![],
+        },
+        shape: records,
+    }
+}
+
+/// 排版后的字形
+#[derive(Debug, Clone)]
+pub(crate) struct TextGlyph {
+    pub(crate) font_id: CharacterId,
+    pub(crate) index: u32,
+    /// 字形相对于文本的变换，包含位置、字号与颜色
+    pub(crate) transform: Transform,
+    /// 加载时细分得到的字形网格
+    pub(crate) shape: Option<Handle<Shape>>,
+}
+
+/// 绘制排版后的字形
+pub(crate) fn render_glyphs(
+    glyphs: &[TextGlyph],
+    context: &mut RenderContext,
+    blend_mode: BlendMode,
+) {
+    for glyph in glyphs {
+        let Some(shape) = &glyph.shape else {
+            continue;
+        };
+        context.transform_stack.push(&glyph.transform);
+        context.render_shape(
+            shape.clone(),
+            context.transform_stack.transform(),
+            blend_mode.into(),
+        );
+        context.transform_stack.pop();
+    }
+}
+
+/// 静态文本（DefineText / DefineText2）
+#[derive(Debug, Clone)]
+pub struct Text {
+    id: CharacterId,
+    base: DisplayObjectBase,
+    bounds: Rectangle<Twips>,
+    glyphs: Vec<TextGlyph>,
+    movie: Arc<SwfMovie>,
+}
+
+impl Text {
+    /// 按 `TextRecord` 排版字形，找不到的字体或字形会被跳过
+    pub fn from_swf_tag(
+        text: swf::Text,
+        fonts: &HashMap<CharacterId, Arc<Font>>,
+        movie: Arc<SwfMovie>,
+    ) -> Self {
+        let matrix = Matrix::from(text.matrix);
+        let mut glyphs = Vec::new();
+        let mut font_id = 0;
+        let mut color = Color::WHITE;
+        let mut height = Twips::ZERO;
+        let mut x = Twips::ZERO;
+        let mut y = Twips::ZERO;
+        for record in text.records {
+            font_id = record.font_id.unwrap_or(font_id);
+            color = record.color.unwrap_or(color);
+            height = record.height.unwrap_or(height);
+            x = record.x_offset.unwrap_or(x);
+            y = record.y_offset.unwrap_or(y);
+            let Some(font) = fonts.get(&font_id) else {
+                continue;
+            };
+            let scale = height.get() as f32 / font.scale();
+            for entry in record.glyphs {
+                if font.glyph(entry.index).is_some() {
+                    glyphs.push(TextGlyph {
+                        font_id,
+                        index: entry.index,
+                        transform: Transform {
+                            matrix: matrix * Matrix::create_box(scale, scale, x, y),
+                            color_transform: ColorTransform::multiply_from(color),
+                        },
+                        shape: None,
+                    });
+                }
+                x += Twips::new(entry.advance);
+            }
+        }
+        Self {
+            id: text.id,
+            base: Default::default(),
+            bounds: text.bounds,
+            glyphs,
+            movie,
+        }
+    }
+
+    pub fn bounds(&self) -> &Rectangle<Twips> {
+        &self.bounds
+    }
+
+    /// 文本用到的字形
+    pub fn used_glyphs(&self) -> impl Iterator<Item = (CharacterId, u32)> + '_ {
+        self.glyphs.iter().map(|glyph| (glyph.font_id, glyph.index))
+    }
+
+    /// 关联加载时细分得到的字形网格
+    pub(crate) fn set_glyph_shapes(&mut self, shapes: &GlyphShapes) {
+        for glyph in &mut self.glyphs {
+            glyph.shape = shapes.get(&(glyph.font_id, glyph.index)).cloned();
+        }
+    }
+}
+
+impl TDisplayObject for Text {
+    fn base(&self) -> &DisplayObjectBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut DisplayObjectBase {
+        &mut self.base
+    }
+
+    fn movie(&self) -> Arc<SwfMovie> {
+        self.movie.clone()
+    }
+
+    fn replace_with(&mut self, id: CharacterId, characters: &HashMap<CharacterId, Character>) {
+        if let Some(Character::Text(text)) = characters.get(&id) {
+            self.id = text.id;
+            self.bounds = text.bounds.clone();
+            self.glyphs = text.glyphs.clone();
+            self.movie = text.movie.clone();
+        }
+    }
+
+    fn self_bounds(&mut self, _context: &mut RenderContext) -> Rectangle<Twips> {
+        self.bounds.clone()
+    }
+
+    fn id(&self) -> CharacterId {
+        self.id
+    }
+
+    fn render_self(&mut self, context: &mut RenderContext, blend_mode: BlendMode) {
+        render_glyphs(&self.glyphs, context, blend_mode);
+    }
+}
+
+impl From<Text> for DisplayObject {
+    fn from(text: Text) -> Self {
+        Self::Text(text)
+    }
+}